@@ -1,64 +1,75 @@
-fn parse(input: &str) -> std::io::Result<Vec<i64>> {
-    input
-        .lines()
-        .map(|line| line.parse::<i64>())
-        .collect::<Result<_, _>>()
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+use std::str::FromStr;
+
+/// A parsed list of sonar depth measurements, so that both parts can be
+/// computed without re-parsing the input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Measurements(Vec<i64>);
+
+impl FromStr for Measurements {
+    type Err = std::io::Error;
+
+    fn from_str(input: &str) -> std::io::Result<Self> {
+        input
+            .lines()
+            .map(|line| line.parse::<i64>())
+            .collect::<Result<_, _>>()
+            .map(Measurements)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
 }
 
-fn num_increases(input: &str) -> std::io::Result<usize> {
-    let mut num_increases = 0;
-    let mut prev: Option<i64> = None;
-    for num in parse(input)? {
-        if let Some(prev) = prev {
-            if num > prev {
-                num_increases += 1;
+impl Measurements {
+    fn increases(&self) -> usize {
+        self.window_increases(1)
+    }
+
+    /// Counts how often the sum of a sliding window of `window` measurements
+    /// is larger than the one before it. An empty window never increases.
+    fn window_increases(&self, window: usize) -> usize {
+        if window == 0 {
+            return 0;
+        }
+        let mut num_increases = 0;
+        let mut prev: Option<i64> = None;
+        for window in self.0.windows(window) {
+            let sum = window.iter().sum();
+            if let Some(prev) = prev {
+                if sum > prev {
+                    num_increases += 1;
+                }
             }
+            prev = Some(sum);
         }
-        prev = Some(num);
+        num_increases
     }
-    Ok(num_increases)
 }
 
-fn unordered_windows<Iter, T, const L: usize>(it: Iter) -> impl Iterator<Item = [T; L]>
-where
-    Iter: Iterator<Item = T>,
-    T: Copy + std::fmt::Debug + Default,
-{
-    let buffer = [T::default(); L];
-    it.scan(
-        (buffer, 0 as usize, false),
-        move |(buffer, idx, filled), item| {
-            buffer[*idx] = item;
-            *idx = *idx + 1;
-            if *idx == L {
-                *filled = true;
-                *idx = 0;
-            }
-            if *filled {
-                Some(Some(buffer.clone()))
-            } else {
-                Some(None)
-            }
-        },
-    )
-    .filter_map(|x| x)
+fn num_increases(input: &str) -> std::io::Result<usize> {
+    Ok(input.parse::<Measurements>()?.increases())
 }
 
 fn num_window_increases(input: &str) -> std::io::Result<usize> {
+    Ok(input.parse::<Measurements>()?.window_increases(3))
+}
+
+/// Computes both parts in a single walk over the measurements.
+///
+/// Two adjacent 3-wide windows share their middle two values, so the later
+/// window is larger exactly when the value entering it is larger than the
+/// value 3 steps back that is leaving.
+fn solve(input: &str) -> std::io::Result<(usize, usize)> {
+    let Measurements(nums) = input.parse()?;
     let mut num_increases = 0;
-    let mut prev: Option<i64> = None;
-    let nums = parse(input)?;
-    for window in unordered_windows::<_, _, 3>(nums.iter().copied()) {
-        let sum = window.iter().sum();
-        if let Some(prev) = prev {
-            if sum > prev {
-                num_increases += 1;
-            }
+    let mut num_window_increases = 0;
+    for (i, &num) in nums.iter().enumerate() {
+        if i >= 1 && num > nums[i - 1] {
+            num_increases += 1;
+        }
+        if i >= 3 && num > nums[i - 3] {
+            num_window_increases += 1;
         }
-        prev = Some(sum);
     }
-    Ok(num_increases)
+    Ok((num_increases, num_window_increases))
 }
 
 #[test]
@@ -95,3 +106,39 @@ fn test_windowed_increases() -> std::io::Result<()> {
     assert_eq!(num_window_increases(include_str!("day1.txt"))?, 1797);
     Ok(())
 }
+
+#[test]
+fn test_measurements() -> std::io::Result<()> {
+    let measurements: Measurements = "199
+200
+208
+210
+200
+207
+240
+269
+260
+263"
+    .parse()?;
+    assert_eq!(measurements.increases(), 7);
+    assert_eq!(measurements.window_increases(3), 5);
+    assert_eq!(measurements.window_increases(0), 0);
+    Ok(())
+}
+
+#[test]
+fn test_solve() -> std::io::Result<()> {
+    let example = "199
+200
+208
+210
+200
+207
+240
+269
+260
+263";
+    assert_eq!(solve(example)?, (7, 5));
+    assert_eq!(solve(include_str!("day1.txt"))?, (1766, 1797));
+    Ok(())
+}
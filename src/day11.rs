@@ -1,6 +1,7 @@
+use crate::grid::parse_grid_of;
 use anyhow::anyhow;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 enum EnergyLevel {
     Value(u8),
     Flashed,
@@ -14,28 +15,29 @@ impl std::str::FromStr for Grid {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> anyhow::Result<Self, Self::Err> {
-        let mut grid = [[EnergyLevel::Value(0); 10]; 10];
-        let mut i = 0;
-        for ch in s.trim().chars() {
-            if ch == '\n' {
-                continue;
-            }
-            if i > 100 {
-                return Err(anyhow!(
-                    "invalid grid size. got more than 100 spaces in grid"
-                ));
-            }
+        let rows = parse_grid_of(s, |ch| {
             let value = ch
                 .to_digit(10)
                 .ok_or_else(|| anyhow!("invalid grid value: {}", ch))?;
-            grid[i / 10][i % 10] = EnergyLevel::Value(value as u8);
-            i += 1;
+            Ok(EnergyLevel::Value(value as u8))
+        })?;
+        if rows.len() != 10 || rows[0].len() != 10 {
+            return Err(anyhow!("invalid grid size. expected a 10x10 grid"));
+        }
+        let mut grid = [[EnergyLevel::Value(0); 10]; 10];
+        for (row, parsed) in grid.iter_mut().zip(rows) {
+            row.copy_from_slice(&parsed);
         }
         Ok(Grid(grid))
     }
 }
 
 impl Grid {
+    /// Advances one step, returning the number of octopuses that flashed.
+    ///
+    /// Every octopus gains energy before any of them flash, and an octopus
+    /// is marked `Flashed` as soon as it goes over 9, so it can't flash again
+    /// (or gain more energy) until the reset at the end of the step.
     fn step(&mut self) -> u64 {
         // first the energy level of each octopus is increased by one
         for row in self.0.iter_mut() {
@@ -131,6 +133,40 @@ impl std::fmt::Display for Grid {
     }
 }
 
+#[test]
+fn test_simultaneous_flashes() {
+    // both 9s reach 10 in the base increment, so both flash, and each pushes
+    // the other past 9 again, but neither gets to flash a second time
+    let mut grid: Grid = "
+9900000000
+0000000000
+0000000000
+0000000000
+0000000000
+0000000000
+0000000000
+0000000000
+0000000000
+0000000000"
+        .parse()
+        .unwrap();
+    assert_eq!(grid.step(), 2);
+    let expected: Grid = "
+0021111111
+3321111111
+1111111111
+1111111111
+1111111111
+1111111111
+1111111111
+1111111111
+1111111111
+1111111111"
+        .parse()
+        .unwrap();
+    assert_eq!(grid.0, expected.0);
+}
+
 fn part_1(input: &str) -> anyhow::Result<u64> {
     let mut grid: Grid = input.parse()?;
     let mut flashes = 0;
@@ -1,3 +1,4 @@
+use crate::grid::parse_grid_of;
 use anyhow::{anyhow, Error, Result};
 use std::{
     collections::{BinaryHeap, HashSet},
@@ -14,14 +15,11 @@ impl FromStr for Cavern {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let mut costs = Vec::new();
-        for line in s.lines() {
-            costs.push(
-                line.chars()
-                    .map(|c| Ok(c.to_digit(10).ok_or(anyhow!("Invalid cost: {}", c))? as u8))
-                    .collect::<Result<Vec<_>>>()?,
-            );
-        }
+        let costs = parse_grid_of(s, |c| {
+            c.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or_else(|| anyhow!("Invalid cost: {}", c))
+        })?;
         Ok(Cavern { costs })
     }
 }
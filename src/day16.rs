@@ -2,9 +2,11 @@ use std::fmt::Debug;
 
 use anyhow::{anyhow, Result};
 
+/// Reads bits from a hex string. Reading moves a cursor forward rather than
+/// consuming the nibbles, so that saving and restoring the reader's place is
+/// cheap.
 struct BitstreamReader {
     nibbles: Vec<u8>,
-    bits_remaining_in_head: u8,
     position: usize,
 }
 
@@ -14,57 +16,56 @@ impl BitstreamReader {
             nibbles: s
                 .trim()
                 .chars()
-                .rev()
                 .map(|c| c.to_digit(16).unwrap() as u8)
                 .collect(),
-            bits_remaining_in_head: 4,
             position: 0,
         }
     }
 
+    /// The number of bits read so far.
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Captures the reader's current place, so that a speculative read can
+    /// be backed out with `restore`.
+    fn save(&self) -> BitstreamSnapshot {
+        BitstreamSnapshot {
+            position: self.position,
+        }
+    }
+
+    fn restore(&mut self, snapshot: BitstreamSnapshot) {
+        self.position = snapshot.position;
+    }
+
+    /// Reads the next `n` bits without consuming them.
+    fn peek_bits(&mut self, n: u8) -> Option<u16> {
+        let snapshot = self.save();
+        let result = self.bits_into_u16(n);
+        self.restore(snapshot);
+        result
+    }
+
     fn next_bit(&mut self) -> Option<bool> {
-        self.bits_remaining_in_head -= 1;
+        let nibble = self.nibbles.get(self.position / 4)?;
+        let offset = self.position % 4;
         self.position += 1;
-        let nibble = self.nibbles.last_mut()?;
-        let result = *nibble & 0b1000 == 0b1000;
-        if self.bits_remaining_in_head == 0 {
-            self.bits_remaining_in_head = 4;
-            self.nibbles.pop();
-        } else {
-            *nibble <<= 1;
-        }
-        Some(result)
+        Some(nibble & (0b1000 >> offset) != 0)
     }
 
-    fn bits_into_u8(&mut self, mut n: u8) -> Option<u8> {
+    fn bits_into_u8(&mut self, n: u8) -> Option<u8> {
         if n > 8 {
             panic!("Can't read more than 8 bits into a u8, asked for {}", n);
         }
-        let mut result = 0;
-        while self.bits_remaining_in_head <= n {
-            result <<= self.bits_remaining_in_head;
-            n -= self.bits_remaining_in_head;
-            let bits = (self.nibbles.pop()? & 0b1111) >> (4 - self.bits_remaining_in_head);
-            result |= bits;
-            self.position += self.bits_remaining_in_head as usize;
-            self.bits_remaining_in_head = 4;
-        }
-        for _ in 0..n {
-            result <<= 1;
-            result |= self.next_bit()? as u8;
-        }
-        Some(result)
+        Some(self.bits_into_u16(n)? as u8)
     }
 
-    fn bits_into_u16(&mut self, mut n: u8) -> Option<u16> {
+    fn bits_into_u16(&mut self, n: u8) -> Option<u16> {
         if n > 16 {
             panic!("Can't read more than 16 bits into a u16, asked for {}", n);
         }
         let mut result = 0;
-        if n > 8 {
-            result |= self.bits_into_u8(8)? as u16;
-            n -= 8;
-        }
         for _ in 0..n {
             result <<= 1;
             result |= self.next_bit()? as u16;
@@ -73,6 +74,11 @@ impl BitstreamReader {
     }
 }
 
+/// A place in a `BitstreamReader` to `restore` to.
+struct BitstreamSnapshot {
+    position: usize,
+}
+
 #[derive(Debug)]
 struct Packet {
     version: u8,
@@ -105,6 +111,31 @@ impl Packet {
     fn value(&self) -> u64 {
         self.content.value()
     }
+
+    /// The number of levels of packets, counting this one. A literal value
+    /// packet has a depth of 1.
+    fn depth(&self) -> usize {
+        match &self.content {
+            PacketContent::LiteralValue(_) => 1,
+            PacketContent::Operator(OperatorPacket { children, .. }) => {
+                1 + children.iter().map(|c| c.depth()).max().unwrap_or(0)
+            }
+        }
+    }
+
+    /// The largest number of direct children of any operator packet in this
+    /// packet's tree.
+    fn max_fanout(&self) -> usize {
+        match &self.content {
+            PacketContent::LiteralValue(_) => 0,
+            PacketContent::Operator(OperatorPacket { children, .. }) => children
+                .iter()
+                .map(|c| c.max_fanout())
+                .max()
+                .unwrap_or(0)
+                .max(children.len()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -205,9 +236,9 @@ impl OperatorPacket {
     }
 
     fn read_n_bits_of_packets(reader: &mut BitstreamReader, num_bits: u16) -> Result<Vec<Packet>> {
-        let goal = reader.position + num_bits as usize;
+        let goal = reader.position() + num_bits as usize;
         let mut children = Vec::new();
-        while reader.position < goal {
+        while reader.position() < goal {
             children.push(Packet::from_bitstream(reader)?);
         }
         Ok(children)
@@ -272,6 +303,33 @@ impl OperatorPacket {
     }
 }
 
+#[test]
+fn test_save_restore() {
+    let mut reader = BitstreamReader::from_hex_str("38006F45291200");
+    reader.bits_into_u8(3).unwrap();
+    let snapshot = reader.save();
+    assert_eq!(reader.position(), 3);
+
+    let first_read = (
+        reader.bits_into_u8(3).unwrap(),
+        reader.bits_into_u16(16).unwrap(),
+    );
+    assert_eq!(reader.position(), 22);
+
+    reader.restore(snapshot);
+    assert_eq!(reader.position(), 3);
+    let second_read = (
+        reader.bits_into_u8(3).unwrap(),
+        reader.bits_into_u16(16).unwrap(),
+    );
+    assert_eq!(first_read, second_read);
+
+    let mut reader = BitstreamReader::from_hex_str("38006F45291200");
+    assert_eq!(reader.peek_bits(3), Some(1));
+    assert_eq!(reader.position(), 0);
+    assert_eq!(reader.bits_into_u8(3), Some(1));
+}
+
 fn part_1(input: &str) -> Result<u64> {
     let mut reader = BitstreamReader::from_hex_str(input);
     let packet = Packet::from_bitstream(&mut reader)?;
@@ -287,7 +345,7 @@ fn test_part_1() {
     let length_type: bool = reader.next_bit().unwrap();
     assert_eq!(version, 1);
     assert_eq!(packet_type, 6);
-    assert_eq!(length_type, false);
+    assert!(!length_type);
 
     assert_eq!(part_1("8A004A801A8002F478").unwrap(), 16);
     assert_eq!(part_1("620080001611562C8802118E34").unwrap(), 12);
@@ -314,3 +372,22 @@ fn test_part_2() {
     assert_eq!(part_2("9C0141080250320F1802104A08").unwrap(), 1);
     assert_eq!(part_2(include_str!("day16.txt")).unwrap(), 902_198_718_880);
 }
+
+#[test]
+fn test_shape() {
+    let parse = |hex| Packet::from_bitstream(&mut BitstreamReader::from_hex_str(hex)).unwrap();
+
+    let literal = parse("D2FE28");
+    assert_eq!(literal.depth(), 1);
+    assert_eq!(literal.max_fanout(), 0);
+
+    // an operator packet that contains an operator packet that contains an
+    // operator packet that contains five literal values
+    let nested = parse("A0016C880162017C3686B18A3D4780");
+    assert_eq!(nested.depth(), 4);
+    assert_eq!(nested.max_fanout(), 5);
+
+    let sum = parse("C200B40A82");
+    assert_eq!(sum.depth(), 2);
+    assert_eq!(sum.max_fanout(), 2);
+}
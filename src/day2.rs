@@ -1,11 +1,13 @@
-use anyhow::{anyhow, Result};
-use std::str::FromStr;
+use anyhow::{anyhow, bail, Context, Result};
+use std::{fmt::Display, str::FromStr};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum Direction {
     Forward,
     Down,
     Up,
+    Left,
+    Right,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -20,17 +22,22 @@ impl FromStr for Command {
     fn from_str(s: &str) -> Result<Self> {
         // parse like "forward 10" or "down 20"
         let mut parts = s.split_whitespace();
-        let direction = match parts.next().unwrap() {
+        let direction = match parts
+            .next()
+            .ok_or_else(|| anyhow!("missing direction in line: {:?}", s))?
+        {
             "forward" => Direction::Forward,
             "down" => Direction::Down,
             "up" => Direction::Up,
-            _ => return Err(anyhow!("invalid direction")),
+            "left" => Direction::Left,
+            "right" => Direction::Right,
+            other => return Err(anyhow!("invalid direction: {}", other)),
         };
         let steps = parts
             .next()
             .ok_or(anyhow!("Expected direction then number"))?
             .parse::<i64>()
-            .map_err(|e| anyhow!("Invalid number: {}", e))?;
+            .with_context(|| format!("Invalid number in command: {}", s))?;
         if parts.next().is_some() {
             return Err(anyhow!("Too many parts: {}", s));
         }
@@ -39,17 +46,181 @@ impl FromStr for Command {
     }
 }
 
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self.direction {
+            Direction::Forward => "forward",
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        };
+        write!(f, "{} {}", direction, self.steps)
+    }
+}
+
+#[test]
+fn test_command_round_trip() {
+    let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2";
+    let commands = input
+        .lines()
+        .map(Command::from_str)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    let rendered = commands
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(rendered, input);
+    let reparsed = rendered
+        .lines()
+        .map(Command::from_str)
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(reparsed, commands);
+
+    // whitespace is normalized away
+    assert_eq!(
+        "  left   7 ".parse::<Command>().unwrap().to_string(),
+        "left 7"
+    );
+}
+
+/// Lazily parses one command per line, tagging errors with the 1-based line
+/// number.
+fn parse_commands(input: &str) -> impl Iterator<Item = Result<Command>> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Command::from_str(line).with_context(|| format!("on line {}", i + 1)))
+}
+
+#[test]
+fn test_parse_commands() {
+    let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+    let commands = parse_commands(input).collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(commands.len(), 6);
+    assert_eq!(
+        commands[1],
+        Command {
+            direction: Direction::Down,
+            steps: 5
+        }
+    );
+
+    let err = parse_commands("up 1\nsideways 2")
+        .collect::<Result<Vec<_>>>()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "on line 2");
+}
+
+/// Like `parse_commands`, but rejects `left` and `right`, which only make
+/// sense for a `Location3D`.
+fn parse_planar_commands(input: &str) -> impl Iterator<Item = Result<Command>> + '_ {
+    parse_commands(input).enumerate().map(|(i, command)| {
+        let command = command?;
+        match command.direction {
+            Direction::Left | Direction::Right => {
+                Err(anyhow!("the 2D submarine can't move sideways: {}", command))
+                    .with_context(|| format!("on line {}", i + 1))
+            }
+            _ => Ok(command),
+        }
+    })
+}
+
+#[test]
+fn test_parse_planar_commands() {
+    let err = part_1("forward 5\nleft 5").unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "on line 2: the 2D submarine can't move sideways: left 5"
+    );
+    assert!(part_2("right 1").is_err());
+    assert!(solve("down 2\nright 1").is_err());
+    assert!(take_commands_scaled::<SimpleLocation>("left 1", 2, 2).is_err());
+}
+
 fn take_commands<Loc>(commands: &str) -> Result<SimpleLocation>
 where
     Loc: Location + Into<SimpleLocation> + Default,
 {
     let mut location = Loc::default();
-    for command in commands.lines() {
-        location.take_command(Command::from_str(command)?);
+    for command in parse_planar_commands(commands) {
+        location.take_command(command?);
+    }
+    Ok(location.into())
+}
+
+/// Like `take_commands`, but `forward` steps are multiplied by
+/// `forward_mult` and `up`/`down` steps by `depth_mult` before being taken.
+fn take_commands_scaled<Loc>(
+    commands: &str,
+    forward_mult: i64,
+    depth_mult: i64,
+) -> Result<SimpleLocation>
+where
+    Loc: Location + Into<SimpleLocation> + Default,
+{
+    let mut location = Loc::default();
+    for command in parse_planar_commands(commands) {
+        let command = command?;
+        let mult = match command.direction {
+            Direction::Forward => forward_mult,
+            Direction::Down | Direction::Up => depth_mult,
+            Direction::Left | Direction::Right => {
+                unreachable!("parse_planar_commands rejects sideways commands")
+            }
+        };
+        location.take_command(Command {
+            direction: command.direction,
+            steps: command.steps * mult,
+        });
     }
     Ok(location.into())
 }
 
+#[test]
+fn test_take_commands_scaled() {
+    let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+    assert_eq!(
+        take_commands_scaled::<SimpleLocation>(input, 1, 1).unwrap(),
+        take_commands::<SimpleLocation>(input).unwrap()
+    );
+    assert_eq!(
+        take_commands_scaled::<TrickyLocation>(input, 1, 1).unwrap(),
+        take_commands::<TrickyLocation>(input).unwrap()
+    );
+
+    let forward_only = "forward 5\nforward 3";
+    assert_eq!(
+        take_commands_scaled::<SimpleLocation>(forward_only, 2, 1).unwrap(),
+        SimpleLocation { depth: 0, x: 16 }
+    );
+    assert_eq!(
+        take_commands_scaled::<SimpleLocation>(input, 1, 3).unwrap(),
+        SimpleLocation { depth: 30, x: 15 }
+    );
+}
+
 trait Location {
     fn take_command(&mut self, command: Command);
 }
@@ -65,12 +236,67 @@ impl Location for SimpleLocation {
             Direction::Forward => self.x += command.steps,
             Direction::Down => self.depth += command.steps,
             Direction::Up => self.depth -= command.steps,
+            // the 2D submarine can't move sideways, and parse_planar_commands
+            // and SubmarineLog::apply reject these before they get here
+            Direction::Left | Direction::Right => {}
+        }
+    }
+}
+
+impl Display for SimpleLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "x={} depth={}", self.x, self.depth)
+    }
+}
+
+/// A submarine that remembers the commands it has taken, so that they can be
+/// rewound.
+#[derive(Debug, Default)]
+struct SubmarineLog {
+    location: SimpleLocation,
+    history: Vec<Command>,
+}
+impl SubmarineLog {
+    /// Takes `command` and records it, or errors without recording it if
+    /// it's a sideways command, which the 2D submarine can't take.
+    fn apply(&mut self, command: Command) -> Result<()> {
+        if let Direction::Left | Direction::Right = command.direction {
+            bail!("the 2D submarine can't move sideways: {}", command);
         }
+        self.location.take_command(command);
+        self.history.push(command);
+        Ok(())
+    }
+
+    /// Reverses the effect of the most recent command, returning it.
+    fn undo(&mut self) -> Option<Command> {
+        let command = self.history.pop()?;
+        self.location.take_command(Command {
+            direction: command.direction,
+            steps: -command.steps,
+        });
+        Some(command)
+    }
+}
+
+/// How `up` and `down` are interpreted.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum AimMode {
+    /// `up` and `down` move the submarine directly (part 1).
+    Simple,
+    /// `up` and `down` adjust the aim, and `forward` dives along it (part 2).
+    Aim,
+}
+
+fn navigate(input: &str, mode: AimMode) -> Result<SimpleLocation> {
+    match mode {
+        AimMode::Simple => take_commands::<SimpleLocation>(input),
+        AimMode::Aim => take_commands::<TrickyLocation>(input),
     }
 }
 
 fn part_1(input: &str) -> Result<i64> {
-    let location = take_commands::<SimpleLocation>(input)?;
+    let location = navigate(input, AimMode::Simple)?;
     Ok(location.x.abs() * location.depth.abs())
 }
 
@@ -92,6 +318,99 @@ forward 2
     assert_eq!(part_1(include_str!("./day2.txt")).unwrap(), 1_561_344);
 }
 
+#[test]
+fn test_undo() {
+    let mut log = SubmarineLog::default();
+    for command in ["forward 8", "down 5", "up 3"] {
+        log.apply(command.parse().unwrap()).unwrap();
+    }
+    assert_eq!(log.location, SimpleLocation { depth: 2, x: 8 });
+
+    let err = log.apply("left 5".parse().unwrap()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "the 2D submarine can't move sideways: left 5"
+    );
+    assert_eq!(log.history.len(), 3);
+
+    assert_eq!(log.undo(), Some("up 3".parse().unwrap()));
+    assert_eq!(log.location, SimpleLocation { depth: 5, x: 8 });
+    assert_eq!(log.undo(), Some("down 5".parse().unwrap()));
+    assert_eq!(log.location, SimpleLocation { depth: 0, x: 8 });
+    assert_eq!(log.undo(), Some("forward 8".parse().unwrap()));
+    assert_eq!(log.location, SimpleLocation::default());
+    assert_eq!(log.undo(), None);
+}
+
+/// A submarine that can also move sideways, with `right` moving towards
+/// positive `y`.
+#[derive(Debug, PartialEq, Eq, Default)]
+struct Location3D {
+    x: i64,
+    y: i64,
+    depth: i64,
+}
+impl Location for Location3D {
+    fn take_command(&mut self, command: Command) {
+        match command.direction {
+            Direction::Forward => self.x += command.steps,
+            Direction::Down => self.depth += command.steps,
+            Direction::Up => self.depth -= command.steps,
+            Direction::Right => self.y += command.steps,
+            Direction::Left => self.y -= command.steps,
+        }
+    }
+}
+
+#[test]
+fn test_3d() {
+    let mut location = Location3D::default();
+    for command in "forward 5
+right 4
+down 5
+left 1
+forward 8
+up 3
+right 2
+down 8"
+        .lines()
+    {
+        location.take_command(command.parse().unwrap());
+    }
+    assert_eq!(
+        location,
+        Location3D {
+            x: 13,
+            y: 5,
+            depth: 10
+        }
+    );
+    assert_eq!(location.x * location.y * location.depth, 650);
+}
+
+#[test]
+fn test_errors() {
+    let err = part_1("forward 5\nbackward 3").unwrap_err();
+    assert_eq!(err.to_string(), "on line 2");
+    assert_eq!(err.root_cause().to_string(), "invalid direction: backward");
+
+    let err = part_1("forward five").unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "on line 1: Invalid number in command: forward five: invalid digit found in string"
+    );
+}
+
+#[test]
+fn test_blank_line() {
+    let err = part_1("forward 5\n\ndown 2").unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "missing direction in line: \"\""
+    );
+    assert!(Command::from_str("   ").is_err());
+}
+
 #[derive(Debug, PartialEq, Eq, Default)]
 struct TrickyLocation {
     depth: i64,
@@ -107,23 +426,50 @@ impl Location for TrickyLocation {
                 self.x += command.steps;
                 self.depth += self.aim * command.steps;
             }
+            // rejected by parse_planar_commands, as for SimpleLocation
+            Direction::Left | Direction::Right => {}
         }
     }
 }
-impl Into<SimpleLocation> for TrickyLocation {
-    fn into(self) -> SimpleLocation {
+impl From<TrickyLocation> for SimpleLocation {
+    fn from(location: TrickyLocation) -> Self {
         SimpleLocation {
-            depth: self.depth,
-            x: self.x,
+            depth: location.depth,
+            x: location.x,
         }
     }
 }
 
+impl Display for TrickyLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "x={} depth={} aim={}", self.x, self.depth, self.aim)
+    }
+}
+
 fn part_2(input: &str) -> Result<i64> {
-    let location = take_commands::<TrickyLocation>(input)?;
+    let location = navigate(input, AimMode::Aim)?;
     Ok(location.x.abs() * location.depth.abs())
 }
 
+#[test]
+fn test_navigate() {
+    let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+    assert_eq!(
+        navigate(input, AimMode::Simple).unwrap(),
+        SimpleLocation { depth: 10, x: 15 }
+    );
+    assert_eq!(
+        navigate(input, AimMode::Aim).unwrap(),
+        SimpleLocation { depth: 60, x: 15 }
+    );
+}
+
 #[test]
 fn test_part2() {
     let input = "forward 5
@@ -141,3 +487,72 @@ forward 2
 
     assert_eq!(part_2(include_str!("./day2.txt")).unwrap(), 1_848_454_425);
 }
+
+#[test]
+fn test_display() {
+    assert_eq!(
+        SimpleLocation { depth: 10, x: 15 }.to_string(),
+        "x=15 depth=10"
+    );
+    assert_eq!(
+        TrickyLocation {
+            depth: 60,
+            x: 15,
+            aim: 10
+        }
+        .to_string(),
+        "x=15 depth=60 aim=10"
+    );
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Day2Answer {
+    part1: i64,
+    part2: i64,
+    final_simple: SimpleLocation,
+    final_tricky: TrickyLocation,
+}
+
+/// Parses the commands once and steers both kinds of submarine with them.
+fn solve(input: &str) -> Result<Day2Answer> {
+    let mut simple = SimpleLocation::default();
+    let mut tricky = TrickyLocation::default();
+    for command in parse_planar_commands(input) {
+        let command = command?;
+        simple.take_command(command);
+        tricky.take_command(command);
+    }
+    Ok(Day2Answer {
+        part1: simple.x.abs() * simple.depth.abs(),
+        part2: tricky.x.abs() * tricky.depth.abs(),
+        final_simple: simple,
+        final_tricky: tricky,
+    })
+}
+
+#[test]
+fn test_solve() {
+    let input = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+    let answer = solve(input).unwrap();
+    assert_eq!(answer.part1, 150);
+    assert_eq!(answer.part2, 900);
+    assert_eq!(answer.final_simple, SimpleLocation { depth: 10, x: 15 });
+    assert_eq!(
+        answer.final_tricky,
+        TrickyLocation {
+            depth: 60,
+            x: 15,
+            aim: 10
+        }
+    );
+
+    let answer = solve(include_str!("./day2.txt")).unwrap();
+    assert_eq!(answer.part1, 1_561_344);
+    assert_eq!(answer.part2, 1_848_454_425);
+}
@@ -1,49 +1,265 @@
-use std::thread;
-
-use anyhow::{anyhow, Result};
-
-fn extract_counts(input: &Vec<String>) -> Result<(Vec<usize>, usize)> {
-    let mut counts = vec![];
-    let mut line_count = 0;
-    for line in input {
-        for (idx, c) in line.chars().enumerate() {
-            let i = c.to_digit(2);
-            // convert option to result
-            let i = i.ok_or_else(|| anyhow::anyhow!("invalid bit char: {}", c))?;
-            if idx >= counts.len() {
-                counts.push(0);
-            }
-            if i == 1 {
-                counts[idx] += 1;
+use std::{io::BufRead, str::FromStr, thread};
+
+use anyhow::{anyhow, Context, Result};
+
+/// How many times a 1 appears in each column of a set of diagnostics.
+#[derive(Debug, PartialEq, Eq)]
+struct BitCounts {
+    /// The number of 1s in each column, most significant bit first.
+    ones: Vec<usize>,
+    line_count: usize,
+    /// The number of bits in every line.
+    width: usize,
+}
+
+fn parse_row(line: &str) -> Result<Vec<bool>> {
+    // underscores and whitespace may be used to group digits
+    line.chars()
+        .filter(|&c| c != '_' && !c.is_whitespace())
+        .map(|c| match c.to_digit(2) {
+            Some(i) => Ok(i == 1),
+            None => Err(anyhow!("invalid bit char: {}", c)),
+        })
+        .collect()
+}
+
+fn width_mismatch(line_idx: usize, found: usize, width: usize, line: &str) -> anyhow::Error {
+    anyhow!(
+        "line {} is {} bits wide, but expected {} bits: {}",
+        line_idx + 1,
+        found,
+        width,
+        line
+    )
+}
+
+/// A grid of diagnostic bits, one row per line of input, with the most
+/// significant bit first. Every row is `width` bits wide.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct BitMatrix {
+    bits: Vec<Vec<bool>>,
+    width: usize,
+}
+
+impl BitMatrix {
+    fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut bits: Vec<Vec<bool>> = Vec::new();
+        let mut width = 0;
+        for (line_idx, line) in lines.into_iter().enumerate() {
+            let row = parse_row(line)?;
+            if line_idx == 0 {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(width_mismatch(line_idx, row.len(), width, line));
             }
+            bits.push(row);
         }
-        line_count += 1;
+        Ok(BitMatrix { bits, width })
+    }
+
+    /// The number of rows with a 1 in the given column.
+    fn column_ones(&self, col: usize) -> usize {
+        self.bits.iter().filter(|row| row[col]).count()
+    }
+
+    /// The given row, read as a binary number.
+    fn to_u64(&self, row: usize) -> u64 {
+        bits_to_u64(&self.bits[row])
+    }
+}
+
+impl FromStr for BitMatrix {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        BitMatrix::from_lines(s.lines())
+    }
+}
+
+#[test]
+fn test_bit_matrix() {
+    let matrix: BitMatrix = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010"
+        .parse()
+        .unwrap();
+    assert_eq!(matrix.bits.len(), 12);
+    assert_eq!(matrix.width, 5);
+    assert_eq!(matrix.column_ones(0), 7);
+    assert_eq!(matrix.column_ones(4), 5);
+    assert_eq!(matrix.to_u64(1), 0b11110);
+
+    assert!("0102".parse::<BitMatrix>().is_err());
+}
+
+#[test]
+fn test_digit_separators() {
+    let separated = ["1_0_1", "0 1_1"].map(String::from);
+    let plain = ["101", "011"].map(String::from);
+    assert_eq!(
+        extract_counts(&separated).unwrap(),
+        extract_counts(&plain).unwrap()
+    );
+    assert_eq!("1011_0110".parse::<BitMatrix>().unwrap().width, 8);
+
+    let err = extract_counts(&["1_0-1".to_string()]).unwrap_err();
+    assert_eq!(err.to_string(), "invalid bit char: -");
+}
+
+fn extract_counts(input: &[String]) -> Result<BitCounts> {
+    let matrix = BitMatrix::from_lines(input.iter().map(String::as_str))?;
+    Ok(BitCounts {
+        ones: (0..matrix.width)
+            .map(|col| matrix.column_ones(col))
+            .collect(),
+        line_count: matrix.bits.len(),
+        width: matrix.width,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct GammaRate(u64);
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct EpsilonRate(u64);
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct OxygenRate(u64);
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct Co2Rate(u64);
+
+impl GammaRate {
+    fn value(self) -> u64 {
+        self.0
+    }
+
+    fn power_consumption(self, epsilon: EpsilonRate) -> u64 {
+        self.value() * epsilon.value()
+    }
+}
+impl EpsilonRate {
+    fn value(self) -> u64 {
+        self.0
+    }
+}
+impl OxygenRate {
+    fn value(self) -> u64 {
+        self.0
+    }
+
+    fn life_support_rating(self, co2: Co2Rate) -> u64 {
+        self.value() * co2.value()
+    }
+}
+impl Co2Rate {
+    fn value(self) -> u64 {
+        self.0
     }
-    Ok((counts, line_count))
-}
-
-fn extract_gamma_and_epsilon(input: &str) -> Result<(u64, u64)> {
-    let lines = input.lines().map(|l| String::from(l)).collect();
-    let (counts, line_count) = extract_counts(&lines)?;
-    // for each bit, if the count at that bit is > half of the number of lines, then it's a 1
-    let mut gamma_rate: u64 = 0;
-    let mut epsilon_rate = 0;
-    for count in counts.iter() {
-        gamma_rate <<= 1;
-        epsilon_rate <<= 1;
-        if count > &(line_count / 2) {
-            gamma_rate += 1;
-        } else {
-            epsilon_rate += 1;
+}
+
+impl BitCounts {
+    fn gamma_and_epsilon(&self) -> (GammaRate, EpsilonRate) {
+        // for each bit, if 1 is at least as common as 0, then it's a 1
+        let mut gamma_rate: u64 = 0;
+        let mut epsilon_rate = 0;
+        for &count in self.ones.iter() {
+            gamma_rate <<= 1;
+            epsilon_rate <<= 1;
+            if 2 * count >= self.line_count {
+                gamma_rate += 1;
+            } else {
+                epsilon_rate += 1;
+            }
         }
+        (GammaRate(gamma_rate), EpsilonRate(epsilon_rate))
     }
-    Ok((gamma_rate, epsilon_rate))
+}
+
+fn extract_gamma_and_epsilon(input: &str) -> Result<(GammaRate, EpsilonRate)> {
+    let lines = input.lines().map(String::from).collect::<Vec<_>>();
+    Ok(extract_counts(&lines)?.gamma_and_epsilon())
+}
+
+/// The number of bits in each line of the given diagnostics.
+fn diagnostic_width(input: &str) -> Result<usize> {
+    Ok(input.parse::<BitMatrix>()?.width)
+}
+
+/// Formats a rate as binary, zero-padded to the diagnostic width.
+fn format_rate(value: u64, width: usize) -> String {
+    format!("{:0width$b}", value, width = width)
+}
+
+#[test]
+fn test_format_rate() {
+    assert_eq!(format_rate(0b00110, 5), "00110");
+    assert_eq!(format_rate(0, 3), "000");
+
+    let input = "00100\n11110\n10110";
+    let width = diagnostic_width(input).unwrap();
+    assert_eq!(width, 5);
+    let (gamma, epsilon) = extract_gamma_and_epsilon(input).unwrap();
+    assert_eq!(format_rate(gamma.value(), width), "10110");
+    assert_eq!(format_rate(epsilon.value(), width), "01001");
 }
 
 fn part_1(input: &str) -> Result<u64> {
     let (gamma_rate, epsilon_rate) = extract_gamma_and_epsilon(input)?;
-    let power_consumption = gamma_rate * epsilon_rate;
-    Ok(power_consumption)
+    Ok(gamma_rate.power_consumption(epsilon_rate))
+}
+
+/// Like `part_1`, but reads diagnostics a line at a time, only keeping the
+/// running count of each column in memory.
+fn part_1_reader<R: BufRead>(reader: R) -> Result<u64> {
+    let mut counts = BitCounts {
+        ones: vec![],
+        line_count: 0,
+        width: 0,
+    };
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let row = parse_row(&line)?;
+        if line_idx == 0 {
+            counts.width = row.len();
+            counts.ones = vec![0; row.len()];
+        } else if row.len() != counts.width {
+            return Err(width_mismatch(line_idx, row.len(), counts.width, &line));
+        }
+        for (count, bit) in counts.ones.iter_mut().zip(row) {
+            if bit {
+                *count += 1;
+            }
+        }
+        counts.line_count += 1;
+    }
+    let (gamma_rate, epsilon_rate) = counts.gamma_and_epsilon();
+    Ok(gamma_rate.power_consumption(epsilon_rate))
+}
+
+#[test]
+fn test_part_1_reader() {
+    use std::io::Cursor;
+    let input = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010";
+    assert_eq!(part_1_reader(Cursor::new(input)).unwrap(), 198);
+    assert!(part_1_reader(Cursor::new("0010\n11110")).is_err());
 }
 
 #[test]
@@ -60,115 +276,161 @@ fn test_part1() {
 11001
 00010
 01010";
-    assert_eq!(extract_gamma_and_epsilon(input).unwrap(), (22, 9));
+    assert_eq!(
+        extract_gamma_and_epsilon(input).unwrap(),
+        (GammaRate(22), EpsilonRate(9))
+    );
     assert_eq!(part_1(input).unwrap(), 198);
 
     assert_eq!(part_1(include_str!("./day3.txt")).unwrap(), 693_486);
 }
 
-fn extract_oxygen_and_co2(input: &str) -> Result<(u64, u64)> {
-    let mut o2_candidates: Vec<String> = input.lines().map(|l| String::from(l)).collect();
-    let mut co2_candidates = o2_candidates.clone();
-
-    let o2_thread = thread::spawn(move || {
-        let mut pos: usize = 0;
-        while o2_candidates.len() > 1 {
-            let (counts, line_count) = extract_counts(&o2_candidates)?;
-            let majority_ones = match counts.get(pos) {
-                Some(&count) => count >= ((line_count as f64) / 2.0).ceil() as usize,
-                None => {
-                    return Err(anyhow!(
-                        "Ran out of diagnostics by the time we looked at bit {} of the oxygen line",
-                        pos
-                    ))
-                }
-            };
-            o2_candidates = o2_candidates
-                .into_iter()
-                .filter(|line| {
-                    let char = line.chars().nth(pos);
-                    if majority_ones {
-                        char == Some('1')
-                    } else {
-                        char == Some('0')
-                    }
-                })
-                .collect();
-            pos += 1;
+#[test]
+fn test_gamma_tie() {
+    // the second column has one 1 and one 0, which counts as a 1 for gamma
+    assert_eq!(
+        extract_gamma_and_epsilon("10\n11").unwrap(),
+        (GammaRate(0b11), EpsilonRate(0b00))
+    );
+    assert_eq!(
+        extract_gamma_and_epsilon("10\n01").unwrap(),
+        (GammaRate(0b11), EpsilonRate(0b00))
+    );
+}
+
+#[test]
+fn test_mismatched_widths() {
+    let err = extract_gamma_and_epsilon("00100\n11110\n1011\n10111").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 3 is 4 bits wide, but expected 5 bits: 1011"
+    );
+    assert!(extract_gamma_and_epsilon("0010\n11110").is_err());
+
+    let lines = ["00100", "11110", "10110"].map(String::from);
+    assert_eq!(
+        extract_counts(&lines).unwrap(),
+        BitCounts {
+            ones: vec![2, 1, 3, 2, 0],
+            line_count: 3,
+            width: 5,
         }
-        let oxygen_rate = match o2_candidates.get(0) {
-            Some(line) => {
-                // parse line as a binary string into a number
-                u64::from_str_radix(line, 2)
-                    .map_err(|_| anyhow!("invalid binary number: {}", line))?
-            }
-            None => {
-                return Err(anyhow!(
-                    "Ran out of diagnostics by the time we looked at bit {} of the oxygen line",
-                    pos
-                ))
-            }
-        };
-        Ok(oxygen_rate)
-    });
-
-    let co2_thread = thread::spawn(move || {
-        let mut pos: usize = 0;
-        while co2_candidates.len() > 1 {
-            let (counts, line_count) = extract_counts(&co2_candidates)?;
-            let majority_zeros = match counts.get(pos) {
-                Some(&count) => count >= ((line_count as f64) / 2.0).ceil() as usize,
-                None => {
-                    return Err(anyhow!(
-                        "Ran out of diagnostics by the time we looked at bit {} of co2 scrubbers",
-                        pos
-                    ))
-                }
-            };
-            co2_candidates = co2_candidates
-                .into_iter()
-                .filter(|line| {
-                    let char = line.chars().nth(pos);
-                    if majority_zeros {
-                        char == Some('0')
-                    } else {
-                        char == Some('1')
-                    }
-                })
-                .collect();
-            pos += 1;
+    );
+}
+
+fn bits_to_u64(bits: &[bool]) -> u64 {
+    bits.iter().fold(0, |acc, &bit| (acc << 1) | bit as u64)
+}
+
+/// Repeatedly narrows down the candidates by their bit at each position, from
+/// most significant to least, until only one remains.
+///
+/// With `keep_most_common`, candidates with the more common bit are kept,
+/// and 1s win ties. Otherwise candidates with the less common bit are kept,
+/// and 0s win ties.
+fn filter_by_bit_criteria(
+    mut candidates: Vec<Vec<bool>>,
+    keep_most_common: bool,
+) -> Result<Vec<bool>> {
+    let width = candidates.first().map_or(0, |bits| bits.len());
+    let mut pos: usize = 0;
+    while candidates.len() > 1 {
+        if pos >= width {
+            return Err(anyhow!(
+                "Still had {} diagnostics left after looking at all {} bits",
+                candidates.len(),
+                width
+            ));
         }
-        let co2_rate = match co2_candidates.get(0) {
-            Some(line) => {
-                // parse line as a binary string into a number
-                u64::from_str_radix(line, 2)
-                    .map_err(|_| anyhow!("invalid binary number: {}", line))?
-            }
-            None => {
-                return Err(anyhow!(
-                    "Ran out of diagnostics by the time we looked at bit {} of c02 scrubbers",
-                    pos
-                ))
-            }
-        };
-        Ok(co2_rate)
-    });
+        let ones = candidates.iter().filter(|bits| bits[pos]).count();
+        let majority_ones = 2 * ones >= candidates.len();
+        candidates.retain(|bits| (bits[pos] == majority_ones) == keep_most_common);
+        pos += 1;
+    }
+    candidates
+        .pop()
+        .ok_or_else(|| anyhow!("No diagnostics to filter"))
+}
+
+#[test]
+fn test_filter_by_bit_criteria() {
+    let candidates = vec![
+        vec![false, true],
+        vec![true, false],
+        vec![true, true],
+        vec![false, false],
+    ];
+    // every column is tied, so the most common criterion takes 1s all the way
+    // down, and the least common criterion takes 0s
+    assert_eq!(
+        filter_by_bit_criteria(candidates.clone(), true).unwrap(),
+        vec![true, true]
+    );
+    assert_eq!(
+        filter_by_bit_criteria(candidates, false).unwrap(),
+        vec![false, false]
+    );
 
-    // for each bit, if the count at that bit is > half of the number of lines, then it's a 1
-    Ok((
-        o2_thread
-            .join()
-            .map_err(|e| anyhow!("Error in o2 thread: {:?}", e))??,
-        co2_thread
-            .join()
-            .map_err(|e| anyhow!("Error in co2 thread: {:?}", e))??,
-    ))
+    assert!(filter_by_bit_criteria(vec![], true).is_err());
+    assert!(filter_by_bit_criteria(vec![vec![true], vec![true]], true).is_err());
+}
+
+/// Computes a single life support rating from the given diagnostics.
+///
+/// `most_common` selects the oxygen generator criterion: keep the lines with
+/// the most common bit in each position, preferring 1 when 1s and 0s are
+/// equally common. Otherwise this uses the CO2 scrubber criterion: keep the
+/// lines with the least common bit, preferring 0 on a tie.
+fn rating(lines: &[String], most_common: bool) -> Result<u64> {
+    let BitMatrix { bits, .. } = BitMatrix::from_lines(lines.iter().map(String::as_str))?;
+    Ok(bits_to_u64(&filter_by_bit_criteria(bits, most_common)?))
+}
+
+#[test]
+fn test_rating() {
+    let lines = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010"
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    assert_eq!(rating(&lines, true).unwrap(), 23);
+    assert_eq!(rating(&lines, false).unwrap(), 10);
+}
+
+fn extract_oxygen_and_co2(input: &str) -> Result<(OxygenRate, Co2Rate)> {
+    let BitMatrix {
+        bits: o2_candidates,
+        ..
+    } = input.parse()?;
+    let co2_candidates = o2_candidates.clone();
+
+    let o2_thread = thread::spawn(move || filter_by_bit_criteria(o2_candidates, true));
+    let co2_thread = thread::spawn(move || filter_by_bit_criteria(co2_candidates, false));
+
+    let oxygen = o2_thread
+        .join()
+        .map_err(|e| anyhow!("Error in o2 thread: {:?}", e))?
+        .context("finding the oxygen generator rating")?;
+    let co2 = co2_thread
+        .join()
+        .map_err(|e| anyhow!("Error in co2 thread: {:?}", e))?
+        .context("finding the CO2 scrubber rating")?;
+    Ok((OxygenRate(bits_to_u64(&oxygen)), Co2Rate(bits_to_u64(&co2))))
 }
 
 fn part_2(input: &str) -> Result<u64> {
     let (oxygen_rate, co2_rate) = extract_oxygen_and_co2(input)?;
-    let power_consumption = oxygen_rate * co2_rate;
-    Ok(power_consumption)
+    Ok(oxygen_rate.life_support_rating(co2_rate))
 }
 
 #[test]
@@ -185,8 +447,26 @@ fn test_part2() {
 11001
 00010
 01010";
-    assert_eq!(extract_oxygen_and_co2(input).unwrap(), (23, 10));
+    assert_eq!(
+        extract_oxygen_and_co2(input).unwrap(),
+        (OxygenRate(23), Co2Rate(10))
+    );
     assert_eq!(part_2(input).unwrap(), 230);
 
     assert_eq!(part_2(include_str!("./day3.txt")).unwrap(), 3379326);
 }
+
+#[test]
+fn test_rate_types() {
+    let gamma = GammaRate(22);
+    let epsilon = EpsilonRate(9);
+    assert_eq!(gamma.value(), 22);
+    assert_eq!(epsilon.value(), 9);
+    assert_eq!(gamma.power_consumption(epsilon), 198);
+
+    let oxygen = OxygenRate(23);
+    let co2 = Co2Rate(10);
+    assert_eq!(oxygen.value(), 23);
+    assert_eq!(co2.value(), 10);
+    assert_eq!(oxygen.life_support_rating(co2), 230);
+}
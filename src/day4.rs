@@ -1,13 +1,21 @@
 use anyhow::{anyhow, Context, Error, Result};
 use std::str::FromStr;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// A `size` by `size` bingo board, stored in row-major order.
+#[derive(Clone, PartialEq, Eq)]
 struct BingoBoard {
-    numbers: [BoardSquare; 25],
+    size: usize,
+    numbers: Vec<BoardSquare>,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum WinningLine {
+    Row(usize),
+    Col(usize),
 }
 
 impl BingoBoard {
-    fn call_number(&mut self, number: u8) {
+    fn call_number(&mut self, number: u16) {
         for square in self.numbers.iter_mut() {
             if square.number == number {
                 square.is_called = true;
@@ -15,125 +23,207 @@ impl BingoBoard {
         }
     }
 
+    /// Unmarks every square, as if no numbers had been called.
+    fn reset(&mut self) {
+        for square in self.numbers.iter_mut() {
+            square.is_called = false;
+        }
+    }
+
     fn wins(&self) -> bool {
-        let rows = [
-            self.numbers[0..5].iter(),
-            self.numbers[5..10].iter(),
-            self.numbers[10..15].iter(),
-            self.numbers[15..20].iter(),
-            self.numbers[20..25].iter(),
-        ];
-        for mut horizontal in rows {
-            if horizontal.all(|square| square.is_called) {
-                return true;
-            }
+        self.winning_line().is_some()
+    }
+
+    /// The first fully called row or column, checking rows first.
+    fn winning_line(&self) -> Option<WinningLine> {
+        let mut rows = self.numbers.chunks(self.size);
+        if let Some(row) = rows.position(|row| row.iter().all(|square| square.is_called)) {
+            return Some(WinningLine::Row(row));
         }
-        let columns = [
-            self.numbers.iter().step_by(5),
-            self.numbers[1..].iter().step_by(5),
-            self.numbers[2..].iter().step_by(5),
-            self.numbers[3..].iter().step_by(5),
-            self.numbers[4..].iter().step_by(5),
-        ];
-        for mut vertical in columns {
-            if vertical.all(|square| square.is_called) {
-                return true;
-            }
+        (0..self.size)
+            .find(|&column| {
+                self.numbers[column..]
+                    .iter()
+                    .step_by(self.size)
+                    .all(|square| square.is_called)
+            })
+            .map(WinningLine::Col)
+    }
+
+    /// Like `wins`, but also counts either of the two main diagonals.
+    fn wins_with_diagonals(&self) -> bool {
+        if self.wins() {
+            return true;
         }
-        false
+        let size = self.size;
+        let down_right = (0..size).all(|i| self.numbers[i * size + i].is_called);
+        let down_left = (0..size).all(|i| self.numbers[i * size + (size - 1 - i)].is_called);
+        down_right || down_left
     }
-}
 
-impl FromStr for BingoBoard {
-    type Err = Error;
+    fn unmarked_sum(&self) -> u64 {
+        self.numbers
+            .iter()
+            .filter(|square| !square.is_called)
+            .map(|square| square.number as u64)
+            .sum()
+    }
 
-    fn from_str(s: &str) -> Result<Self> {
-        // a bingo board is a 5x5 grid of whitespace spearate ascii numbers
-        let mut numbers = [BoardSquare::default(); 25];
-        let mut i = 0;
-        for str in s.trim().split_whitespace() {
-            if i > 25 {
-                return Err(anyhow!(
-                    "invalid bingo board size. expected 25 spaces, got {}",
-                    i
-                ));
-            }
-            numbers[i] = BoardSquare {
-                number: str.parse()?,
-                is_called: false,
-            };
-            i += 1;
-        }
-        if i != 25 {
+    /// The sum of the uncalled squares times the number that was just called.
+    fn score(&self, last_called: u16) -> u64 {
+        self.unmarked_sum() * last_called as u64
+    }
+}
+
+impl BingoBoard {
+    fn from_squares(numbers: Vec<BoardSquare>) -> Result<Self> {
+        let size = (numbers.len() as f64).sqrt() as usize;
+        if numbers.is_empty() || size * size != numbers.len() {
             return Err(anyhow!(
-                "invalid bingo board size. expected 25 spaces, got {}",
-                i
+                "invalid bingo board size. expected a square number of spaces, got {}",
+                numbers.len()
             ));
         }
+        Ok(BingoBoard { size, numbers })
+    }
+
+    /// Parses a board in the same grid layout as the normal input, except
+    /// that called squares are prefixed with a `*`, e.g. `*14`.
+    fn from_grid(s: &str) -> Result<Self> {
+        let numbers = s
+            .split_whitespace()
+            .map(|str| {
+                let (is_called, number) = match str.strip_prefix('*') {
+                    Some(number) => (true, number),
+                    None => (false, str),
+                };
+                Ok(BoardSquare {
+                    number: number
+                        .parse()
+                        .with_context(|| format!("invalid square: {}", str))?,
+                    is_called,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        BingoBoard::from_squares(numbers)
+    }
+}
+
+impl FromStr for BingoBoard {
+    type Err = Error;
 
-        Ok(BingoBoard { numbers })
+    fn from_str(s: &str) -> Result<Self> {
+        // a bingo board is an NxN grid of whitespace separated ascii numbers
+        let numbers = s
+            .split_whitespace()
+            .map(|str| {
+                Ok(BoardSquare {
+                    number: str.parse()?,
+                    is_called: false,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        BingoBoard::from_squares(numbers)
+    }
+}
+impl BingoBoard {
+    /// The column width needed to line up every number on the board, which
+    /// is at least 2 to match the puzzle input.
+    fn number_width(&self) -> usize {
+        let largest = self.numbers.iter().map(|square| square.number).max();
+        largest.map_or(2, |largest| largest.to_string().len().max(2))
     }
 }
+
 impl std::fmt::Debug for BingoBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for (i, square) in self.numbers.iter().enumerate() {
-            if i % 5 == 0 {
+            if i % self.size == 0 {
                 writeln!(f)?;
             }
-            write!(f, "{:?} ", square)?;
+            write!(f, "{:width$?} ", square, width = self.number_width())?;
         }
         write!(f, "\n\n")
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+/// Writes the board in the same layout as the puzzle input, without marking
+/// which squares have been called.
+impl std::fmt::Display for BingoBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.numbers.chunks(self.size).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let row = row
+                .iter()
+                .map(|square| format!("{:width$}", square.number, width = self.number_width()));
+            write!(f, "{}", row.collect::<Vec<_>>().join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Default)]
 struct BoardSquare {
-    number: u8,
+    number: u16,
     is_called: bool,
 }
+/// Pads the number to the formatter's width, or 2 if none is given.
 impl std::fmt::Debug for BoardSquare {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let width = f.width().unwrap_or(2);
         if self.is_called {
             // bold self.number using terminal escape codes
-            write!(f, "\x1B[1;31m{:2 }\x1B[0m", self.number)
+            write!(f, "\x1B[1;31m{:width$}\x1B[0m", self.number, width = width)
         } else {
-            write!(f, "{:2 }", self.number)
-        }
-    }
-}
-impl Default for BoardSquare {
-    fn default() -> Self {
-        BoardSquare {
-            number: 0,
-            is_called: false,
+            write!(f, "{:width$}", self.number, width = width)
         }
     }
 }
 
+#[derive(Clone)]
 struct Part1Problem {
-    numbers: Vec<u8>,
+    numbers: Vec<u16>,
     bingo_boards: Vec<BingoBoard>,
 }
 impl FromStr for Part1Problem {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self> {
-        // first line is a list of numbers, comma separated, then a list of bingo boards separated by two newlines
-
-        let (first_line, rest) = input
-            .split_once("\n\n")
-            .ok_or(anyhow!("Expected input to start with two newlines"))?;
+        // first line is a list of numbers, comma separated, then a list of
+        // bingo boards. sections are separated by one or more blank lines,
+        // and lines may end in either \n or \r\n
+        let mut sections = vec![];
+        let mut section = String::new();
+        for line in input.lines() {
+            if line.trim().is_empty() {
+                if !section.is_empty() {
+                    sections.push(std::mem::take(&mut section));
+                }
+            } else {
+                section.push_str(line);
+                section.push('\n');
+            }
+        }
+        if !section.is_empty() {
+            sections.push(section);
+        }
+        let mut sections = sections.into_iter();
+        let first_line = sections
+            .next()
+            .ok_or(anyhow!("Expected input to start with a line of numbers"))?;
 
-        let numbers: Vec<u8> = first_line
+        let numbers: Vec<u16> = first_line
+            .trim()
             .split(',')
             .map(|s| {
-                s.parse::<u8>()
+                s.parse::<u16>()
                     .map_err(|_| anyhow!("could not parse number {} from first line of input", s))
             })
-            .collect::<Result<Vec<u8>>>()?;
+            .collect::<Result<Vec<u16>>>()?;
 
-        let bingo_boards: Vec<BingoBoard> = rest
-            .split("\n\n")
+        let bingo_boards: Vec<BingoBoard> = sections
             .enumerate()
             .map(|(i, s)| {
                 s.parse()
@@ -147,20 +237,119 @@ impl FromStr for Part1Problem {
         })
     }
 }
+impl std::fmt::Display for Part1Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let numbers = self.numbers.iter().map(|n| n.to_string());
+        write!(f, "{}", numbers.collect::<Vec<_>>().join(","))?;
+        for board in self.bingo_boards.iter() {
+            write!(f, "\n\n{}", board)?;
+        }
+        Ok(())
+    }
+}
 impl Part1Problem {
-    fn get_first_winning_board_and_number(&mut self) -> Option<(BingoBoard, u8)> {
-        for number in self.numbers.iter() {
-            for bingo_board in self.bingo_boards.iter_mut() {
-                bingo_board.call_number(*number);
+    /// Returns the first winning board and the number that made it win.
+    ///
+    /// If one number completes several boards at once, the board with the
+    /// highest unmarked sum wins, with ties going to the board that came
+    /// first in the input.
+    fn get_first_winning_board_and_number(&mut self) -> Option<(BingoBoard, u16)> {
+        let (index, number) = self.first_winner()?;
+        Some((self.bingo_boards[index].clone(), number))
+    }
+
+    /// Like `get_first_winning_board_and_number`, but returns the index of
+    /// the winning board in `bingo_boards` rather than a copy of it.
+    fn first_winner(&mut self) -> Option<(usize, u16)> {
+        let (winners, number) = self.first_winner_indices()?;
+        Some((self.break_tie(&winners)?, number))
+    }
+
+    /// Picks the winner with the highest unmarked sum, or the first in input
+    /// order if that's tied too.
+    fn break_tie(&self, winners: &[usize]) -> Option<usize> {
+        let mut winners = winners.iter().copied();
+        let mut best = winners.next()?;
+        for index in winners {
+            if self.bingo_boards[index].unmarked_sum() > self.bingo_boards[best].unmarked_sum() {
+                best = index;
+            }
+        }
+        Some(best)
+    }
+
+    /// Returns every board that wins on the first winning number, in input
+    /// order, along with that number.
+    fn first_winners(&mut self) -> Option<(Vec<BingoBoard>, u16)> {
+        let (winners, number) = self.first_winner_indices()?;
+        let winners = winners
+            .into_iter()
+            .map(|index| self.bingo_boards[index].clone())
+            .collect();
+        Some((winners, number))
+    }
+
+    fn first_winner_indices(&mut self) -> Option<(Vec<usize>, u16)> {
+        for &number in self.numbers.iter() {
+            let mut winners = Vec::new();
+            for (index, bingo_board) in self.bingo_boards.iter_mut().enumerate() {
+                bingo_board.call_number(number);
                 if bingo_board.wins() {
-                    return Some((bingo_board.clone(), *number));
+                    winners.push(index);
                 }
             }
+            if !winners.is_empty() {
+                return Some((winners, number));
+            }
         }
         None
     }
 
-    fn get_last_winning_board_and_number(&mut self) -> Option<(BingoBoard, u8)> {
+    /// Calls numbers until any board wins, returning the index of the
+    /// winning board and the number that completed it. This is just
+    /// `first_winner`, so ties are broken the same way and no board will have
+    /// any later numbers marked.
+    fn play_until_first_win(&mut self) -> Option<(usize, u16)> {
+        self.first_winner()
+    }
+
+    /// Calls every number, returning each board's index and the number it
+    /// first won on, in the order that they won. Boards that win on the same
+    /// number are listed in input order, and boards that never win are left
+    /// out.
+    fn winning_order(&mut self) -> Vec<(usize, u16)> {
+        let mut order = Vec::new();
+        let mut has_won = vec![false; self.bingo_boards.len()];
+        for &number in self.numbers.iter() {
+            for (index, bingo_board) in self.bingo_boards.iter_mut().enumerate() {
+                bingo_board.call_number(number);
+                if !has_won[index] && bingo_board.wins() {
+                    has_won[index] = true;
+                    order.push((index, number));
+                }
+            }
+        }
+        order
+    }
+
+    /// Returns the indices of boards that still won't have won once every
+    /// number has been called. The boards themselves are left untouched.
+    fn never_winning_boards(&self) -> Vec<usize> {
+        self.bingo_boards
+            .iter()
+            .enumerate()
+            .filter(|(_, board)| {
+                let mut board = (*board).clone();
+                for &number in self.numbers.iter() {
+                    board.call_number(number);
+                }
+                !board.wins()
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    fn get_last_winning_board_and_number(&mut self) -> Option<(BingoBoard, u16)> {
         let mut active_boards = self.bingo_boards.clone();
         for number in self.numbers.iter() {
             let mut last_board = None;
@@ -174,8 +363,8 @@ impl Part1Problem {
             // let last_board = active_boards.drain_filter(|b| b.wins()).last();
             // which only checks each board once for winning, and efficiently removes winning boards from the vector
             // but we can't use it because it's unstable, and I don't want these AoC solutions to bit rot
-            active_boards = active_boards.into_iter().filter(|b| !b.wins()).collect();
-            if active_boards.len() == 0 {
+            active_boards.retain(|b| !b.wins());
+            if active_boards.is_empty() {
                 if let Some(board) = last_board {
                     return Some((board, *number));
                 } else {
@@ -193,14 +382,7 @@ fn part_1(input: &str) -> Result<u64> {
         .get_first_winning_board_and_number()
         .ok_or(anyhow!("no winning board"))?;
 
-    let unmarked_squares_sum: u64 = board
-        .numbers
-        .iter()
-        .filter(|square| !square.is_called)
-        .map(|square| square.number as u64)
-        .sum();
-
-    Ok(number as u64 * unmarked_squares_sum)
+    Ok(board.score(number))
 }
 
 fn part_2(input: &str) -> Result<u64> {
@@ -209,14 +391,7 @@ fn part_2(input: &str) -> Result<u64> {
         .get_last_winning_board_and_number()
         .ok_or(anyhow!("no final winning board??"))?;
 
-    let unmarked_squares_sum: u64 = board
-        .numbers
-        .iter()
-        .filter(|square| !square.is_called)
-        .map(|square| square.number as u64)
-        .sum();
-
-    Ok(number as u64 * unmarked_squares_sum)
+    Ok(board.score(number))
 }
 
 const EXAMPLE_INPUT: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
@@ -253,6 +428,78 @@ fn test_part1() {
     );
 }
 
+#[test]
+fn test_play_until_first_win() {
+    let mut problem: Part1Problem = EXAMPLE_INPUT.parse().unwrap();
+    let (expected_board, expected_number) = problem
+        .clone()
+        .get_first_winning_board_and_number()
+        .unwrap();
+    let (index, number) = problem.play_until_first_win().unwrap();
+    assert_eq!(index, 2);
+    assert_eq!(number, expected_number);
+    assert_eq!(problem.bingo_boards[index], expected_board);
+}
+
+#[test]
+fn test_first_winner() {
+    let mut problem: Part1Problem = EXAMPLE_INPUT.parse().unwrap();
+    assert_eq!(problem.first_winner(), Some((2, 24)));
+    assert_eq!(problem.bingo_boards[2].unmarked_sum(), 188);
+}
+
+#[test]
+fn test_score() {
+    let board = BingoBoard::from_grid(
+        "
+*14 *21 *17 *24  *4
+ 10  16  15  *9  19
+ 18   8 *23  26  20
+ 22 *11  13   6  *5
+ *2  *0  12   3  *7",
+    )
+    .unwrap();
+    assert_eq!(board.score(24), 4_512);
+}
+
+#[test]
+fn test_winning_order() {
+    let mut problem: Part1Problem = EXAMPLE_INPUT.parse().unwrap();
+    let order = problem.clone().winning_order();
+    assert_eq!(order, vec![(2, 24), (0, 16), (1, 13)]);
+    assert_eq!(order.first(), problem.clone().first_winner().as_ref());
+
+    let (last_board, last_number) = problem.get_last_winning_board_and_number().unwrap();
+    assert_eq!(order.last(), Some(&(1, last_number)));
+    assert_eq!(last_board.score(last_number), 1_924);
+}
+
+#[test]
+fn test_display() {
+    let mut problem: Part1Problem = EXAMPLE_INPUT.parse().unwrap();
+    let rendered = problem.to_string();
+    assert!(rendered.starts_with("7,4,9,"));
+    assert_eq!(rendered.matches("\n\n").count(), 3);
+    assert_eq!(rendered, EXAMPLE_INPUT);
+
+    // called squares aren't rendered
+    problem.get_first_winning_board_and_number();
+    assert_eq!(problem.to_string(), EXAMPLE_INPUT);
+}
+
+#[test]
+fn test_separators() {
+    let crlf = EXAMPLE_INPUT.replace('\n', "\r\n");
+    assert_eq!(part_1(&crlf).unwrap(), 4_512);
+
+    let spaced = format!("\n{}\n\n", EXAMPLE_INPUT.replace("\n\n", "\n\n \n\n"));
+    assert_eq!(part_1(&spaced).unwrap(), 4_512);
+    assert_eq!(
+        spaced.parse::<Part1Problem>().unwrap().to_string(),
+        EXAMPLE_INPUT
+    );
+}
+
 #[test]
 fn test_part2() {
     assert_eq!(
@@ -266,3 +513,203 @@ fn test_part2() {
         21_184
     );
 }
+
+#[test]
+fn test_simultaneous_winners() {
+    // the 5 completes the top row of the first board and the left column of
+    // the second board at the same time
+    let mut problem: Part1Problem = "1,2,3,4,5
+
+ 1  2  3  4  5
+10 11 12 13 14
+15 16 17 18 19
+20 21 22 23 24
+25 26 27 28 29
+
+ 1 30 31 32 33
+ 2 34 35 36 37
+ 3 38 39 40 41
+ 4 42 43 44 45
+ 5 46 47 48 49"
+        .parse()
+        .unwrap();
+    let (winners, number) = problem.clone().first_winners().unwrap();
+    assert_eq!(number, 5);
+    assert_eq!(winners.len(), 2);
+    assert_eq!(winners[0].unmarked_sum(), 390);
+    assert_eq!(winners[1].unmarked_sum(), 790);
+
+    assert_eq!(
+        problem.clone().play_until_first_win(),
+        problem.clone().first_winner()
+    );
+    assert_eq!(problem.clone().play_until_first_win(), Some((1, 5)));
+
+    let (board, number) = problem.get_first_winning_board_and_number().unwrap();
+    assert_eq!(number, 5);
+    assert_eq!(board, winners[1]);
+}
+
+#[test]
+fn test_4x4_board() {
+    let mut problem: Part1Problem = "1,2,3,4,9,10,11,12
+
+ 1  2  3  4
+ 5  6  7  8
+ 9 10 11 12
+13 14 15 16"
+        .parse()
+        .unwrap();
+    let (board, number) = problem.get_first_winning_board_and_number().unwrap();
+    assert_eq!(board.size, 4);
+    assert_eq!(number, 4);
+    assert_eq!(
+        board.unmarked_sum(),
+        5 + 6 + 7 + 8 + 9 + 10 + 11 + 12 + 13 + 14 + 15 + 16
+    );
+
+    assert!("1 2 3".parse::<BingoBoard>().is_err());
+    assert!("".parse::<BingoBoard>().is_err());
+}
+
+#[test]
+fn test_diagonal_wins() {
+    let board_str = "
+ 1  2  3  4  5
+ 6  7  8  9 10
+11 12 13 14 15
+16 17 18 19 20
+21 22 23 24 25";
+    let mut board: BingoBoard = board_str.parse().unwrap();
+    for number in [1, 7, 13, 19, 25] {
+        board.call_number(number);
+    }
+    assert!(!board.wins());
+    assert!(board.wins_with_diagonals());
+
+    let mut board: BingoBoard = board_str.parse().unwrap();
+    for number in [5, 9, 13, 17, 21] {
+        board.call_number(number);
+    }
+    assert!(!board.wins());
+    assert!(board.wins_with_diagonals());
+
+    let mut board: BingoBoard = board_str.parse().unwrap();
+    for number in [1, 7, 13, 19] {
+        board.call_number(number);
+    }
+    assert!(!board.wins_with_diagonals());
+}
+
+#[test]
+fn test_from_grid() {
+    let board = BingoBoard::from_grid(
+        "
+ *1  2  3  4  5
+  6  7  8  9 10
+ 11 12 *13 14 15
+ 16 17 18 19 20
+ 21 22 23 24 *25",
+    )
+    .unwrap();
+    assert_eq!(board.size, 5);
+    assert_eq!(
+        board
+            .numbers
+            .iter()
+            .filter(|square| square.is_called)
+            .map(|square| square.number)
+            .collect::<Vec<_>>(),
+        vec![1, 13, 25]
+    );
+    let board = BingoBoard::from_grid("*1 *2 3 *4").unwrap();
+    assert_eq!(
+        board
+            .numbers
+            .iter()
+            .filter(|square| square.is_called)
+            .count(),
+        3
+    );
+    assert!(BingoBoard::from_grid("*1 ** 3 4").is_err());
+}
+
+#[test]
+fn test_winning_line() {
+    let board_str = "
+ 1  2  3  4  5
+ 6  7  8  9 10
+11 12 13 14 15
+16 17 18 19 20
+21 22 23 24 25";
+    let mut board: BingoBoard = board_str.parse().unwrap();
+    assert_eq!(board.winning_line(), None);
+    for number in [11, 12, 13, 14, 15] {
+        board.call_number(number);
+    }
+    assert_eq!(board.winning_line(), Some(WinningLine::Row(2)));
+
+    let mut board: BingoBoard = board_str.parse().unwrap();
+    for number in [4, 9, 14, 19, 24] {
+        board.call_number(number);
+    }
+    assert_eq!(board.winning_line(), Some(WinningLine::Col(3)));
+}
+
+#[test]
+fn test_never_winning_boards() {
+    let problem: Part1Problem = EXAMPLE_INPUT.parse().unwrap();
+    assert_eq!(problem.never_winning_boards(), Vec::<usize>::new());
+
+    // 99 is never called, and it's on every row and column of the second
+    // board
+    let problem: Part1Problem = "1,2,3,4,5,6,7,8,9
+
+1 2 3
+4 5 6
+7 8 9
+
+99  1  2
+ 3 99  4
+ 5  6 99"
+        .parse()
+        .unwrap();
+    assert_eq!(problem.never_winning_boards(), vec![1]);
+    assert!(problem.bingo_boards.iter().all(|board| !board.wins()));
+}
+
+#[test]
+fn test_large_numbers() {
+    let mut problem: Part1Problem = "1000,300,256,999
+
+1000  300
+ 256  999"
+        .parse()
+        .unwrap();
+    let (board, number) = problem.get_first_winning_board_and_number().unwrap();
+    assert_eq!(number, 300);
+    assert_eq!(board.score(number), (256 + 999) * 300);
+
+    let board: BingoBoard = "1000  300\n   7  999".parse().unwrap();
+    assert_eq!(board.to_string(), "1000  300\n   7  999");
+    assert_eq!(format!("{:?}", board), "\n1000  300 \n   7  999 \n\n");
+}
+
+#[test]
+fn test_reset() {
+    let mut board: BingoBoard = "
+ 1  2  3
+ 4  5  6
+ 7  8  9"
+        .parse()
+        .unwrap();
+    let fresh = board.clone();
+    for number in [1, 4, 7] {
+        board.call_number(number);
+    }
+    assert!(board.wins());
+    board.reset();
+    assert!(!board.wins());
+    assert!(board.numbers.iter().all(|square| !square.is_called));
+    assert_eq!(board, fresh);
+}
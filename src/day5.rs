@@ -1,9 +1,10 @@
 use anyhow::{self, Context, Result};
 use rayon::{
-    iter::{IntoParallelIterator, ParallelIterator},
+    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
     str::ParallelString,
 };
 use std::{
+    collections::HashMap,
     fmt::{Display, Formatter},
     str::FromStr,
 };
@@ -43,6 +44,14 @@ impl FromStr for LineSegment {
             .ok_or_else(|| anyhow::anyhow!("Invalid line segment: {}", s))?;
         let start = start.parse::<Point>().context("start")?;
         let end = end.parse::<Point>().context("end")?;
+        // only horizontal, vertical, and 45° diagonal lines are supported
+        let (dx, dy) = (end.x - start.x, end.y - start.y);
+        if dx != 0 && dy != 0 && dx.abs() != dy.abs() {
+            return Err(anyhow::anyhow!(
+                "Line segment is neither straight nor a 45° diagonal: {}",
+                s
+            ));
+        }
         Ok(LineSegment { start, end })
     }
 }
@@ -50,6 +59,67 @@ impl LineSegment {
     fn is_straight(&self) -> bool {
         self.start.x == self.end.x || self.start.y == self.end.y
     }
+
+    /// The number of grid points the line covers, including both ends.
+    ///
+    /// For lines that aren't horizontal, vertical, or 45° diagonals, this is
+    /// the longer of the line's width and height (plus one), i.e. how many
+    /// points `points` yields.
+    fn length(&self) -> i64 {
+        let dx = (self.end.x - self.start.x).abs();
+        let dy = (self.end.y - self.start.y).abs();
+        dx.max(dy) + 1
+    }
+
+    /// Every grid point on the line, from start to end inclusive. Only
+    /// makes sense for horizontal, vertical, and 45° diagonal lines.
+    fn points(&self) -> impl Iterator<Item = Point> {
+        let dx = (self.end.x - self.start.x).signum();
+        let dy = (self.end.y - self.start.y).signum();
+        let start = self.start;
+        (0..self.length()).map(move |i| Point {
+            x: start.x + i * dx,
+            y: start.y + i * dy,
+        })
+    }
+}
+
+#[test]
+fn test_rejects_other_angles() {
+    let err = "0,0 -> 2,1".parse::<LineSegment>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Line segment is neither straight nor a 45° diagonal: 0,0 -> 2,1"
+    );
+    assert!("0,0 -> 2,-2".parse::<LineSegment>().is_ok());
+    assert!(part_2("0,0 -> 0,3\n0,0 -> 2,1").is_err());
+}
+
+#[test]
+fn test_length() {
+    let length = |s: &str| s.parse::<LineSegment>().unwrap().length();
+    assert_eq!(length("0,9 -> 5,9"), 6);
+    assert_eq!(length("7,4 -> 7,0"), 5);
+    assert_eq!(length("8,0 -> 0,8"), 9);
+    assert_eq!(length("3,3 -> 3,3"), 1);
+}
+
+#[test]
+fn test_points() {
+    let diagonal: LineSegment = "0,0 -> 2,2".parse().unwrap();
+    assert_eq!(
+        diagonal.points().collect::<Vec<_>>(),
+        vec![
+            Point { x: 0, y: 0 },
+            Point { x: 1, y: 1 },
+            Point { x: 2, y: 2 },
+        ]
+    );
+    let vertical: LineSegment = "7,4 -> 7,0".parse().unwrap();
+    assert_eq!(vertical.points().count(), 5);
+    assert_eq!(vertical.points().last(), Some(Point { x: 7, y: 0 }));
+    let single: LineSegment = "3,3 -> 3,3".parse().unwrap();
+    assert_eq!(single.points().count(), 1);
 }
 
 struct Board {
@@ -68,50 +138,87 @@ impl Board {
     }
 
     fn add_line(&mut self, line: LineSegment) {
-        // insert all points in the line
-        let (mut x, mut y) = (line.start.x, line.start.y);
-        let (mut dx, mut dy) = (line.end.x - line.start.x, line.end.y - line.start.y);
-        if dx > 0 {
-            dx = 1;
-        } else if dx < 0 {
-            dx = -1;
+        for point in line.points() {
+            let index = self.bounds.index(point.x, point.y);
+            self.points[index] = self.points[index].saturating_add(1);
         }
-        if dy > 0 {
-            dy = 1;
-        } else if dy < 0 {
-            dy = -1;
+    }
+
+    /// The number of points covered by at least `n` lines.
+    fn count_at_least(&self, n: u8) -> usize {
+        self.points.par_iter().filter(|&&i| i >= n).count()
+    }
+
+    /// Renders the board as a plain (ASCII) PGM image, one pixel per point.
+    /// Uncovered points are white, and the most covered points are black.
+    fn to_pgm(&self) -> String {
+        let width = self.bounds.max_x - self.bounds.min_x + 1;
+        let height = self.bounds.max_y - self.bounds.min_y + 1;
+        let max_count = self.points.iter().copied().max().unwrap_or(0).max(1) as u32;
+        let mut pgm = format!("P2\n{} {}\n255\n", width, height);
+        for y in self.bounds.min_y..=self.bounds.max_y {
+            let row = (self.bounds.min_x..=self.bounds.max_x).map(|x| {
+                let count = self.points[self.bounds.index(x, y)] as u32;
+                (255 - count * 255 / max_count).to_string()
+            });
+            pgm.push_str(&row.collect::<Vec<_>>().join(" "));
+            pgm.push('\n');
         }
-        loop {
-            self.points[self.bounds.index(x, y)] =
-                self.points[self.bounds.index(x, y)].saturating_add(1);
-            if x == line.end.x && y == line.end.y {
-                break;
+        pgm
+    }
+
+    /// Like the `Display` output, but points covered by at least `hot` lines
+    /// are drawn as a bold red `#`.
+    fn display_with_threshold(&self, hot: u8) -> String {
+        let mut out = String::new();
+        for y in self.bounds.min_y..=self.bounds.max_y {
+            for x in self.bounds.min_x..=self.bounds.max_x {
+                let count = self.points[self.bounds.index(x, y)];
+                if count >= hot {
+                    out.push_str("\x1B[1;31m#\x1B[0m");
+                } else {
+                    write_count(&mut out, count).unwrap();
+                }
             }
-            x += dx;
-            y += dy;
+            out.push('\n');
         }
+        out
     }
 
     fn combine(&mut self, other: Board) {
-        for (mine, theirs) in self.points.iter_mut().zip(other.points.into_iter()) {
+        for (mine, theirs) in self.points.iter_mut().zip(other.points) {
             *mine = mine.saturating_add(theirs);
         }
     }
 }
 
+/// A board that only stores the points that lines actually cover, for when
+/// the lines are spread far apart.
+#[derive(Default)]
+struct SparseBoard {
+    points: HashMap<Point, u8>,
+}
+
+impl SparseBoard {
+    fn add_line(&mut self, line: LineSegment) {
+        for point in line.points() {
+            let count = self.points.entry(point).or_insert(0);
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// The number of points covered by at least `n` lines.
+    fn count_at_least(&self, n: u8) -> usize {
+        self.points.values().filter(|&&i| i >= n).count()
+    }
+}
+
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         for y in self.bounds.min_y..=self.bounds.max_y {
             for x in self.bounds.min_x..=self.bounds.max_x {
                 let index = self.bounds.index(x, y);
-                let count = self.points[index];
-                if count > 10 {
-                    write!(f, "X")?;
-                } else if count > 0 {
-                    write!(f, "{}", count)?;
-                } else {
-                    write!(f, ".")?;
-                }
+                write_count(f, self.points[index])?;
             }
             writeln!(f)?;
         }
@@ -119,6 +226,27 @@ impl Display for Board {
     }
 }
 
+fn write_count(f: &mut impl std::fmt::Write, count: u8) -> std::fmt::Result {
+    if count > 10 {
+        write!(f, "X")
+    } else if count > 0 {
+        write!(f, "{}", count)
+    } else {
+        write!(f, ".")
+    }
+}
+
+#[test]
+fn test_display_with_threshold() {
+    let board = build_board("0,0 -> 2,0\n1,0 -> 1,1\n1,1 -> 1,0", true).unwrap();
+    assert_eq!(board.to_string(), "131\n.2.\n");
+    assert_eq!(
+        board.display_with_threshold(3),
+        "1\x1B[1;31m#\x1B[0m1\n.2.\n"
+    );
+    assert_eq!(board.display_with_threshold(4), "131\n.2.\n");
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 struct Bounds {
     min_x: i64,
@@ -129,21 +257,66 @@ struct Bounds {
 
 impl Bounds {
     fn area(&self) -> usize {
-        (self.max_x - self.min_x + 1) as usize * (self.max_y - self.min_y + 1) as usize
+        ((self.max_x - self.min_x + 1) as usize)
+            .saturating_mul((self.max_y - self.min_y + 1) as usize)
+    }
+
+    fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
     }
 
+    /// Maps a point to its index in a board's `points`.
+    ///
+    /// Points are stored column-major: all of the points with `min_x`, from
+    /// `min_y` to `max_y`, then all of the points with `min_x + 1`, and so
+    /// on. This means indexes sort the same way that `Point`s do.
     fn index(&self, x: i64, y: i64) -> usize {
-        (x - self.min_x) as usize * (self.max_y - self.min_y + 1) as usize
-            + (y - self.min_y) as usize
+        debug_assert!(
+            (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y),
+            "({}, {}) is out of bounds {:?}",
+            x,
+            y,
+            self
+        );
+        (x - self.min_x) as usize * self.height() + (y - self.min_y) as usize
+    }
+
+    /// The inverse of `index`.
+    fn point_of(&self, index: usize) -> Point {
+        Point {
+            x: self.min_x + (index / self.height()) as i64,
+            y: self.min_y + (index % self.height()) as i64,
+        }
     }
 }
 
+#[test]
+fn test_index_round_trip() {
+    let bounds = Bounds {
+        min_x: -2,
+        max_x: 3,
+        min_y: 5,
+        max_y: 8,
+    };
+    for i in 0..bounds.area() {
+        let point = bounds.point_of(i);
+        assert_eq!(bounds.index(point.x, point.y), i);
+    }
+    assert_eq!(bounds.point_of(0), Point { x: -2, y: 5 });
+    assert_eq!(bounds.point_of(1), Point { x: -2, y: 6 });
+    assert_eq!(bounds.point_of(bounds.area() - 1), Point { x: 3, y: 8 });
+}
+
 fn get_bounds(lines: &[LineSegment]) -> Bounds {
+    let first = match lines.first() {
+        Some(line) => line.start,
+        None => Point { x: 0, y: 0 },
+    };
     let mut bounds = Bounds {
-        min_x: 0,
-        max_x: 0,
-        min_y: 0,
-        max_y: 0,
+        min_x: first.x,
+        max_x: first.x,
+        min_y: first.y,
+        max_y: first.y,
     };
     for line in lines {
         bounds.min_x = bounds.min_x.min(line.start.x.min(line.end.x));
@@ -154,23 +327,60 @@ fn get_bounds(lines: &[LineSegment]) -> Bounds {
     bounds
 }
 
-fn part_1(input: &str) -> Result<usize> {
+#[test]
+fn test_negative_bounds() {
+    let input = "-3,-3 -> -3,2
+-5,0 -> -1,0
+-4,-1 -> -2,1";
     let line_segments = input
+        .lines()
+        .map(|line| line.parse::<LineSegment>())
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        get_bounds(&line_segments),
+        Bounds {
+            min_x: -5,
+            max_x: -1,
+            min_y: -3,
+            max_y: 2,
+        }
+    );
+    assert_eq!(part_1(input).unwrap(), 1);
+    assert_eq!(part_2(input).unwrap(), 1);
+
+    // the bounds shouldn't stretch to include the origin
+    let far_away = [
+        "100,100 -> 100,102".parse::<LineSegment>().unwrap(),
+        "99,101 -> 101,101".parse::<LineSegment>().unwrap(),
+    ];
+    assert_eq!(get_bounds(&far_away).area(), 9);
+}
+
+fn parse_line_segments(input: &str) -> Result<Vec<LineSegment>> {
+    input
         .par_split('\n')
-        // parse the line segments
-        .map(|line| line.parse::<LineSegment>().context("Part 1 input"))
-        .collect::<Result<Vec<_>>>()?;
+        .map(|line| line.parse::<LineSegment>())
+        .collect()
+}
+
+/// Builds a board from every line segment in the input, or just the straight
+/// ones if `include_diagonals` is false.
+fn build_board(input: &str, include_diagonals: bool) -> Result<Board> {
+    let line_segments = parse_line_segments(input)?;
+    Ok(build_board_from(line_segments, include_diagonals))
+}
 
+fn build_board_from(line_segments: Vec<LineSegment>, include_diagonals: bool) -> Board {
     let bounds = get_bounds(&line_segments);
 
-    let board = line_segments
+    line_segments
         .into_par_iter()
         // group the segments into chunks and combine those chunks into boards
         .fold(
             || Board::from_bounds(bounds),
-            |board, line| {
-                let (line, mut board) = (line, board);
-                if line.is_straight() {
+            |mut board, line| {
+                if include_diagonals || line.is_straight() {
                     board.add_line(line);
                 }
                 board
@@ -183,9 +393,278 @@ fn part_1(input: &str) -> Result<usize> {
                 l.combine(r);
                 l
             },
-        );
-    let count_at_least_two = board.points.into_par_iter().filter(|&i| i > 1).count();
-    Ok(count_at_least_two)
+        )
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Point3 {
+    x: i64,
+    y: i64,
+    z: i64,
+}
+impl FromStr for Point3 {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(',');
+        let mut next = |name: &'static str| -> Result<i64> {
+            parts
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("Invalid point: {:?}", s))?
+                .parse()
+                .context(name)
+        };
+        let point = Point3 {
+            x: next("x")?,
+            y: next("y")?,
+            z: next("z")?,
+        };
+        if parts.next().is_some() {
+            return Err(anyhow::anyhow!("Invalid point: {:?}", s));
+        }
+        Ok(point)
+    }
+}
+
+/// A line through 3D space. Lines may move along any combination of axes,
+/// so long as they move the same distance along each of them, so that they
+/// pass cleanly through grid points.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct LineSegment3 {
+    start: Point3,
+    end: Point3,
+}
+impl FromStr for LineSegment3 {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once(" -> ")
+            .ok_or_else(|| anyhow::anyhow!("Invalid line segment: {}", s))?;
+        let start = start.parse::<Point3>().context("start")?;
+        let end = end.parse::<Point3>().context("end")?;
+        let line = LineSegment3 { start, end };
+        let deltas = [end.x - start.x, end.y - start.y, end.z - start.z];
+        let steps = line.length() - 1;
+        if deltas.iter().any(|d| *d != 0 && d.abs() != steps) {
+            return Err(anyhow::anyhow!(
+                "Line segment is neither straight nor diagonal: {}",
+                s
+            ));
+        }
+        Ok(line)
+    }
+}
+impl LineSegment3 {
+    /// The number of grid points the line covers, including both ends.
+    fn length(&self) -> i64 {
+        let dx = (self.end.x - self.start.x).abs();
+        let dy = (self.end.y - self.start.y).abs();
+        let dz = (self.end.z - self.start.z).abs();
+        dx.max(dy).max(dz) + 1
+    }
+
+    /// Every grid point on the line, from start to end inclusive.
+    fn points(&self) -> impl Iterator<Item = Point3> {
+        let dx = (self.end.x - self.start.x).signum();
+        let dy = (self.end.y - self.start.y).signum();
+        let dz = (self.end.z - self.start.z).signum();
+        let start = self.start;
+        (0..self.length()).map(move |i| Point3 {
+            x: start.x + i * dx,
+            y: start.y + i * dy,
+            z: start.z + i * dz,
+        })
+    }
+}
+
+/// The number of points covered by at least two 3D lines.
+fn count_overlaps_3d(input: &str) -> Result<usize> {
+    let mut points: HashMap<Point3, u8> = HashMap::new();
+    for line in input.lines() {
+        let line = line.parse::<LineSegment3>()?;
+        for point in line.points() {
+            let count = points.entry(point).or_insert(0);
+            *count = count.saturating_add(1);
+        }
+    }
+    Ok(points.values().filter(|&&i| i > 1).count())
+}
+
+#[test]
+fn test_3d() {
+    let input = "0,0,0 -> 4,4,4
+4,0,0 -> 0,4,4";
+    assert_eq!(count_overlaps_3d(input).unwrap(), 1);
+
+    let line: LineSegment3 = "2,0,5 -> 2,3,5".parse().unwrap();
+    assert_eq!(line.length(), 4);
+    assert_eq!(line.points().nth(1), Some(Point3 { x: 2, y: 1, z: 5 }));
+    assert_eq!(
+        count_overlaps_3d("0,0,0 -> 0,0,3\n0,0,1 -> 0,0,5\n0,0,2 -> 2,2,2").unwrap(),
+        3
+    );
+
+    assert!("0,0,0 -> 1,2,1".parse::<LineSegment3>().is_err());
+    assert!("0,0 -> 1,1".parse::<LineSegment3>().is_err());
+    assert!("0,0,0,0 -> 1,1,1,1".parse::<LineSegment3>().is_err());
+}
+
+/// Boards covering more cells than this are tracked with a `SparseBoard`
+/// rather than allocating every cell up front.
+const SPARSE_AREA_THRESHOLD: usize = 1 << 24;
+
+/// Like `count_points_with_at_least`, but switches to a `SparseBoard` when
+/// the lines are spread out enough that a dense board would be wasteful.
+fn count_points_with_at_least_any_size(
+    input: &str,
+    n: u8,
+    include_diagonals: bool,
+) -> Result<usize> {
+    let line_segments = parse_line_segments(input)?;
+    if get_bounds(&line_segments).area() <= SPARSE_AREA_THRESHOLD {
+        return Ok(build_board_from(line_segments, include_diagonals).count_at_least(n));
+    }
+    let mut board = SparseBoard::default();
+    for line in line_segments {
+        if include_diagonals || line.is_straight() {
+            board.add_line(line);
+        }
+    }
+    Ok(board.count_at_least(n))
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2";
+
+#[test]
+fn test_sparse_board() {
+    let input = "0,0 -> 0,2
+-1,1 -> 1,1
+1000000000,1000000000 -> 1000000000,1000000003
+999999999,999999999 -> 1000000002,1000000002";
+    let line_segments = parse_line_segments(input).unwrap();
+    assert!(get_bounds(&line_segments).area() > SPARSE_AREA_THRESHOLD);
+    assert_eq!(
+        count_points_with_at_least_any_size(input, 2, false).unwrap(),
+        1
+    );
+    assert_eq!(
+        count_points_with_at_least_any_size(input, 2, true).unwrap(),
+        2
+    );
+
+    let mut board = SparseBoard::default();
+    for line in line_segments {
+        board.add_line(line);
+    }
+    assert_eq!(board.count_at_least(2), 2);
+    assert_eq!(board.count_at_least(1), (3 + 3 - 1) + (4 + 4 - 1));
+
+    // small inputs still use the dense board, with the same results
+    assert_eq!(
+        count_points_with_at_least_any_size(EXAMPLE, 2, true).unwrap(),
+        12
+    );
+}
+
+#[test]
+fn test_to_pgm() {
+    let board = build_board("0,0 -> 3,0\n1,0 -> 1,1", true).unwrap();
+    let pgm = board.to_pgm();
+    let mut lines = pgm.lines();
+    assert_eq!(lines.next(), Some("P2"));
+    assert_eq!(lines.next(), Some("4 2"));
+    assert_eq!(lines.next(), Some("255"));
+    assert_eq!(lines.next(), Some("128 0 128 128"));
+    assert_eq!(lines.next(), Some("255 128 255 255"));
+    assert_eq!(lines.next(), None);
+}
+
+/// Every point covered by at least two lines, in sorted order.
+fn overlap_points(input: &str, include_diagonals: bool) -> Result<Vec<Point>> {
+    let board = build_board(input, include_diagonals)?;
+    // indexes are in the same order as points, so this comes out sorted
+    Ok(board
+        .points
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 1)
+        .map(|(index, _)| board.bounds.point_of(index))
+        .collect())
+}
+
+#[test]
+fn test_overlap_points() {
+    let straight = overlap_points(EXAMPLE, false).unwrap();
+    assert_eq!(
+        straight,
+        vec![
+            Point { x: 0, y: 9 },
+            Point { x: 1, y: 9 },
+            Point { x: 2, y: 9 },
+            Point { x: 3, y: 4 },
+            Point { x: 7, y: 4 },
+        ]
+    );
+    assert_eq!(straight.len(), part_1(EXAMPLE).unwrap());
+    assert_eq!(overlap_points(EXAMPLE, true).unwrap().len(), 12);
+    assert_eq!(
+        overlap_points(EXAMPLE, true).unwrap().len(),
+        part_2(EXAMPLE).unwrap()
+    );
+}
+
+/// The number of points covered by at least `n` lines.
+fn count_points_with_at_least(input: &str, n: u8, include_diagonals: bool) -> Result<usize> {
+    Ok(build_board(input, include_diagonals)?.count_at_least(n))
+}
+
+/// The number of points covered by exactly `exactly` lines.
+///
+/// Counts saturate at `u8::MAX`, so `exactly == 255` counts the points
+/// covered by 255 or more lines.
+fn count_points_exactly(input: &str, exactly: u8, include_diagonals: bool) -> Result<usize> {
+    let board = build_board(input, include_diagonals)?;
+    Ok(board.points.par_iter().filter(|&&i| i == exactly).count())
+}
+
+#[test]
+fn test_count_points_exactly() {
+    // of the 12 points covered at least twice, 2 are covered three times
+    assert_eq!(count_points_exactly(EXAMPLE, 2, true).unwrap(), 10);
+    assert_eq!(count_points_exactly(EXAMPLE, 2, false).unwrap(), 5);
+    assert_eq!(count_points_exactly(EXAMPLE, 3, true).unwrap(), 2);
+
+    let many = vec!["0,0 -> 0,1"; 300].join("\n");
+    assert_eq!(count_points_exactly(&many, 255, false).unwrap(), 2);
+}
+
+#[test]
+fn test_count_points_with_at_least() {
+    assert_eq!(count_points_with_at_least(EXAMPLE, 2, true).unwrap(), 12);
+    assert_eq!(count_points_with_at_least(EXAMPLE, 2, false).unwrap(), 5);
+    // 4,4 and 6,4 are each covered by three lines
+    assert_eq!(count_points_with_at_least(EXAMPLE, 3, true).unwrap(), 2);
+    assert_eq!(count_points_with_at_least(EXAMPLE, 3, false).unwrap(), 0);
+    assert_eq!(count_points_with_at_least(EXAMPLE, 1, false).unwrap(), 21);
+}
+
+/// The number of points covered by at least two lines, ignoring diagonal
+/// lines unless `include_diagonals` is set.
+fn count_overlaps(input: &str, include_diagonals: bool) -> Result<usize> {
+    count_points_with_at_least(input, 2, include_diagonals)
+}
+
+fn part_1(input: &str) -> Result<usize> {
+    count_overlaps(input, false).context("Part 1 input")
 }
 
 #[test]
@@ -210,35 +689,7 @@ fn test_part_1() {
 }
 
 fn part_2(input: &str) -> Result<usize> {
-    let line_segments = input
-        .par_split('\n')
-        // parse the line segments
-        .map(|line| line.parse::<LineSegment>().context("Part 2 input"))
-        .collect::<Result<Vec<_>>>()?;
-
-    let bounds = get_bounds(&line_segments);
-
-    let board = line_segments
-        .into_par_iter()
-        // group the segments into chunks and combine those chunks into boards
-        .fold(
-            || Board::from_bounds(bounds),
-            |board, line| {
-                let (line, mut board) = (line, board);
-                board.add_line(line);
-                board
-            },
-        )
-        // combine those boards down into one
-        .reduce(
-            || Board::from_bounds(bounds),
-            |mut l, r| {
-                l.combine(r);
-                l
-            },
-        );
-    let count_at_least_two = board.points.into_par_iter().filter(|&i| i > 1).count();
-    Ok(count_at_least_two)
+    count_overlaps(input, true).context("Part 2 input")
 }
 
 #[test]
@@ -267,3 +718,21 @@ fn test_part_2() {
     // let big_input = big_input_seed.repeat(10_000);
     // assert_eq!(part_2(&big_input.trim()).unwrap(), 168274);
 }
+
+fn part_diagonals_only(input: &str) -> Result<usize> {
+    let mut line_segments = parse_line_segments(input).context("Diagonals input")?;
+    line_segments.retain(|line| !line.is_straight());
+    Ok(build_board_from(line_segments, true).count_at_least(2))
+}
+
+#[test]
+fn test_part_diagonals_only() {
+    assert_eq!(part_diagonals_only(EXAMPLE).unwrap(), 4);
+    if cfg!(debug_assertions) {
+        return; // skip test in debug mode, it's slow when it's unoptimized
+    }
+    assert_eq!(
+        part_diagonals_only(include_str!("./day5.txt")).unwrap(),
+        5_908
+    );
+}
@@ -1,28 +1,455 @@
-use std::collections::VecDeque;
-
 use anyhow::{Context, Result};
 use num_bigint::BigUint;
+use rayon::prelude::*;
 
 fn count_lanternfish(input: &str, num_days: u64) -> Result<BigUint> {
-    let init: [BigUint; 9] = Default::default();
-    let mut num_fish_each_day_until_spawn: VecDeque<BigUint> = VecDeque::from(init);
-    for days in input.split(',') {
-        let days = days.parse().context("parsing input number")?;
-        if days > 6 {
+    Ok(count_from_histogram(timer_histogram(input, 0)?, num_days))
+}
+
+/// Counts lanternfish starting from the number of fish with each timer value.
+fn count_from_histogram(counts: [BigUint; 9], num_days: u64) -> BigUint {
+    let mut school = School(counts);
+    for _ in 0..num_days {
+        school.step();
+    }
+    school.total()
+}
+
+#[test]
+fn test_count_from_histogram() {
+    let mut counts: [BigUint; 9] = Default::default();
+    counts[0] = 10u64.into();
+    // all ten fish spawn on the first day, so the population doubles
+    assert_eq!(count_from_histogram(counts.clone(), 0), 10u64.into());
+    assert_eq!(count_from_histogram(counts, 1), 20u64.into());
+
+    let example = [0u64, 1, 1, 2, 1, 0, 0, 0, 0].map(BigUint::from);
+    assert_eq!(count_from_histogram(example, 80), 5_934u64.into());
+}
+
+/// Counts lanternfish where a fish that has just spawned resets its timer to
+/// `reset_to`, and a newborn fish starts with a timer of `newborn_delay`.
+fn count_lanternfish_params(
+    input: &str,
+    num_days: u64,
+    reset_to: usize,
+    newborn_delay: usize,
+) -> Result<BigUint> {
+    ReproductionModel::new(reset_to, newborn_delay)?.simulate(input, num_days)
+}
+
+/// How a species of fish reproduces: a fish that has just spawned resets its
+/// timer to `reset_to`, and a newborn fish starts with a timer of
+/// `newborn_delay`.
+///
+/// `reset_to` is never more than `newborn_delay`, so every timer fits in a
+/// buffer of `newborn_delay + 1` buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ReproductionModel {
+    reset_to: usize,
+    newborn_delay: usize,
+}
+
+const LANTERNFISH: ReproductionModel = ReproductionModel {
+    reset_to: 6,
+    newborn_delay: 8,
+};
+
+impl ReproductionModel {
+    fn new(reset_to: usize, newborn_delay: usize) -> Result<Self> {
+        if reset_to > newborn_delay {
             anyhow::bail!(
-                "input number is too large! Expected at most 6 but got {}",
-                days
+                "fish can't reset to {} when newborns start at {}",
+                reset_to,
+                newborn_delay
             );
         }
-        num_fish_each_day_until_spawn[days] += Into::<BigUint>::into(1u64);
+        Ok(ReproductionModel {
+            reset_to,
+            newborn_delay,
+        })
+    }
+
+    fn simulate(&self, input: &str, num_days: u64) -> Result<BigUint> {
+        Ok(self.buckets(input, num_days)?.into_iter().sum())
+    }
+
+    /// Returns the number of fish with each timer value after `num_days`.
+    fn buckets(&self, input: &str, num_days: u64) -> Result<Vec<BigUint>> {
+        let mut num_fish_each_day_until_spawn = vec![BigUint::default(); self.newborn_delay + 1];
+        for days in parse_timers(input, self.reset_to)? {
+            num_fish_each_day_until_spawn[days] += Into::<BigUint>::into(1u64);
+        }
+        for _ in 0..num_days {
+            self.step(&mut num_fish_each_day_until_spawn);
+        }
+
+        Ok(num_fish_each_day_until_spawn)
+    }
+
+    /// Advances `counts`, the number of fish with each timer value, by one
+    /// day.
+    fn step<T: Clone + std::ops::AddAssign>(&self, counts: &mut [T]) {
+        self.try_step(counts, |count, num_spawning| {
+            *count += num_spawning;
+            Some(())
+        });
+    }
+
+    /// Like `step`, but uses `add` to add the fish that just spawned to those
+    /// already at `reset_to`, stopping early if it returns `None`.
+    fn try_step<T: Clone>(
+        &self,
+        counts: &mut [T],
+        add: impl FnOnce(&mut T, T) -> Option<()>,
+    ) -> Option<()> {
+        // the spawning fish move from the front to the back, where they
+        // represent the newborns
+        counts.rotate_left(1);
+        let num_spawning = counts[self.newborn_delay].clone();
+        add(&mut counts[self.reset_to], num_spawning)
+    }
+}
+
+#[test]
+fn test_reproduction_model() {
+    let faster_adults = ReproductionModel::new(5, 8).unwrap();
+    let totals: Vec<BigUint> = [1, 6, 7, 10]
+        .into_iter()
+        .map(|days| faster_adults.simulate("0", days).unwrap())
+        .collect();
+    assert_eq!(totals, [2u64, 2, 3, 4].map(BigUint::from));
+
+    // no childhood, so the population doubles every 3 days
+    let short_cycle = ReproductionModel::new(2, 2).unwrap();
+    assert_eq!(short_cycle.simulate("0", 1).unwrap(), 2u64.into());
+    assert_eq!(short_cycle.simulate("0", 4).unwrap(), 4u64.into());
+    assert_eq!(short_cycle.simulate("0", 7).unwrap(), 8u64.into());
+    assert!(short_cycle.simulate("3", 1).is_err());
+
+    assert_eq!(
+        LANTERNFISH.simulate("3,4,3,1,2", 80).unwrap(),
+        5_934u64.into()
+    );
+    assert!(ReproductionModel::new(9, 8).is_err());
+}
+
+/// Parses the comma separated list of fish timers, ignoring surrounding
+/// whitespace and empty entries (e.g. from a trailing newline).
+fn parse_timers(input: &str, max: usize) -> Result<Vec<usize>> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|days| !days.is_empty())
+        .map(|days| {
+            let days: usize = days.parse().context("parsing input number")?;
+            if days > max {
+                anyhow::bail!(
+                    "input number is too large! Expected at most {} but got {}",
+                    max,
+                    days
+                );
+            }
+            Ok(days)
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_timers() {
+    assert_eq!(
+        count_lanternfish("3,4,3,1,2\n", 18).unwrap(),
+        count_lanternfish("3,4,3,1,2", 18).unwrap()
+    );
+    assert_eq!(count_lanternfish_u128(" 3, 4,3,1,2,\n", 18).unwrap(), 26);
+    assert!(parse_timers("3,7", 6).is_err());
+    assert!(parse_timers("3,x", 6).is_err());
+}
+
+/// Returns the number of fish with each timer value after `num_days`, indexed
+/// by timer.
+fn timer_histogram(input: &str, num_days: u64) -> Result<[BigUint; 9]> {
+    Ok(LANTERNFISH
+        .buckets(input, num_days)?
+        .try_into()
+        .expect("there are newborn_delay + 1 buckets"))
+}
+
+#[test]
+fn test_timer_histogram() {
+    let histogram = timer_histogram("3,4,3,1,2", 18).unwrap();
+    assert_eq!(histogram.iter().sum::<BigUint>(), 26u64.into());
+    assert_eq!(histogram, [3u64, 5, 3, 2, 2, 1, 5, 1, 4].map(BigUint::from));
+}
+
+/// A school of lanternfish, as the number of fish with each timer value, that
+/// can be advanced one day at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct School([BigUint; 9]);
+
+impl School {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(School(timer_histogram(input, 0)?))
+    }
+
+    fn step(&mut self) {
+        LANTERNFISH.step(&mut self.0);
+    }
+
+    fn total(&self) -> BigUint {
+        self.0.iter().sum()
+    }
+}
+
+#[test]
+fn test_school() {
+    let mut school = School::parse("3,4,3,1,2").unwrap();
+    assert_eq!(school.total(), 5u64.into());
+    school.step();
+    assert_eq!(school.total(), 5u64.into());
+    school.step();
+    assert_eq!(school.total(), 6u64.into());
+    for _ in 2..80 {
+        school.step();
+    }
+    assert_eq!(school.total(), 5_934u64.into());
+}
+
+/// Returns the total population on each day from day 0 through `num_days`.
+fn population_curve(input: &str, num_days: u64) -> Result<Vec<BigUint>> {
+    let mut school = School::parse(input)?;
+    let mut curve = vec![school.total()];
+    for _ in 0..num_days {
+        school.step();
+        curve.push(school.total());
+    }
+    Ok(curve)
+}
+
+#[test]
+fn test_population_curve() {
+    let curve = population_curve("3,4,3,1,2", 18).unwrap();
+    assert_eq!(curve.len(), 19);
+    assert_eq!(curve[0], 5u64.into());
+    assert_eq!(curve[2], 6u64.into());
+    assert_eq!(curve[18], 26u64.into());
+}
+
+/// Counts the fish spawned over the course of `num_days`.
+fn births(input: &str, num_days: u64) -> Result<BigUint> {
+    let mut school = School::parse(input)?;
+    let mut births = BigUint::default();
+    for _ in 0..num_days {
+        births += &school.0[0];
+        school.step();
     }
+    Ok(births)
+}
+
+#[test]
+fn test_births() {
+    let initial: BigUint = 5u64.into();
+    assert_eq!(
+        births("3,4,3,1,2", 18).unwrap(),
+        count_lanternfish("3,4,3,1,2", 18).unwrap() - initial
+    );
+    assert_eq!(births("3,4,3,1,2", 0).unwrap(), BigUint::default());
+}
+
+#[test]
+fn test_count_lanternfish_params() {
+    assert_eq!(
+        count_lanternfish_params("3,4,3,1,2", 80, 6, 8).unwrap(),
+        5_934u64.into()
+    );
+    // with newborns that are immediately adults, every fish doubles each
+    // 7 days
+    assert_eq!(
+        count_lanternfish_params("0", 14, 6, 6).unwrap(),
+        4u64.into()
+    );
+    assert!(count_lanternfish_params("3", 1, 9, 8).is_err());
+}
+
+/// Like `count_lanternfish`, but with fixed-width integers, which is much
+/// faster and plenty big enough for the puzzle's day counts.
+fn count_lanternfish_u128(input: &str, num_days: u64) -> Result<u128> {
+    let mut num_fish_each_day_until_spawn = histogram_u128(input)?;
     for _ in 0..num_days {
-        let num_spawning = num_fish_each_day_until_spawn.pop_front().unwrap();
-        num_fish_each_day_until_spawn[6] += &num_spawning;
-        num_fish_each_day_until_spawn.push_back(num_spawning);
+        step_u128(&mut num_fish_each_day_until_spawn);
     }
 
-    Ok(num_fish_each_day_until_spawn.into_iter().sum())
+    Ok(num_fish_each_day_until_spawn.iter().sum())
+}
+
+fn histogram_u128(input: &str) -> Result<[u128; 9]> {
+    let mut num_fish_each_day_until_spawn = [0u128; 9];
+    for days in parse_timers(input, 6)? {
+        num_fish_each_day_until_spawn[days] += 1;
+    }
+    Ok(num_fish_each_day_until_spawn)
+}
+
+fn step_u128(num_fish_each_day_until_spawn: &mut [u128; 9]) {
+    LANTERNFISH.step(num_fish_each_day_until_spawn);
+}
+
+/// Like `count_lanternfish_u128`, but returns an error rather than wrapping
+/// if the population grows too large for a `u128`.
+///
+/// The population grows by about 9% a day, so this happens at around day
+/// 1,000. For the example input, day 998 is the last that fits.
+fn try_count_lanternfish_u128(input: &str, num_days: u64) -> Result<u128> {
+    let mut num_fish_each_day_until_spawn = histogram_u128(input)?;
+    for day in 1..=num_days {
+        LANTERNFISH
+            .try_step(&mut num_fish_each_day_until_spawn, |count, num_spawning| {
+                *count = count.checked_add(num_spawning)?;
+                Some(())
+            })
+            .with_context(|| format!("population overflows u128 at day {}", day))?;
+    }
+
+    num_fish_each_day_until_spawn
+        .iter()
+        .try_fold(0u128, |total, &count| total.checked_add(count))
+        .with_context(|| format!("population overflows u128 at day {}", num_days))
+}
+
+#[test]
+fn test_try_count_lanternfish_u128() {
+    assert_eq!(
+        try_count_lanternfish_u128("3,4,3,1,2", 256).unwrap(),
+        26_984_457_539
+    );
+    assert_eq!(
+        try_count_lanternfish_u128("3,4,3,1,2", 998).unwrap(),
+        count_lanternfish_u128("3,4,3,1,2", 998).unwrap()
+    );
+    assert_eq!(
+        try_count_lanternfish_u128("3,4,3,1,2", 999)
+            .unwrap_err()
+            .to_string(),
+        "population overflows u128 at day 999"
+    );
+}
+
+/// Returns the first day on which the population is at least double its
+/// initial size.
+fn days_to_double(input: &str) -> Result<u64> {
+    let mut num_fish_each_day_until_spawn = histogram_u128(input)?;
+    let initial: u128 = num_fish_each_day_until_spawn.iter().sum();
+    if initial == 0 {
+        anyhow::bail!("an empty school never doubles");
+    }
+    let mut day = 0;
+    while num_fish_each_day_until_spawn.iter().sum::<u128>() < initial * 2 {
+        step_u128(&mut num_fish_each_day_until_spawn);
+        day += 1;
+    }
+    Ok(day)
+}
+
+#[test]
+fn test_days_to_double() {
+    let day = days_to_double("3,4,3,1,2").unwrap();
+    assert_eq!(day, 5);
+    assert!(count_lanternfish_u128("3,4,3,1,2", day).unwrap() >= 10);
+    assert!(count_lanternfish_u128("3,4,3,1,2", day - 1).unwrap() < 10);
+    assert!(days_to_double("").is_err());
+}
+
+#[test]
+fn test_count_lanternfish_u128() {
+    assert_eq!(count_lanternfish_u128("3,4,3,1,2", 18).unwrap(), 26);
+    assert_eq!(count_lanternfish_u128("3,4,3,1,2", 80).unwrap(), 5_934);
+    assert_eq!(
+        count_lanternfish_u128(include_str!("./day6.txt"), 80).unwrap(),
+        380_243
+    );
+    assert_eq!(
+        count_lanternfish_u128(include_str!("./day6.txt"), 256).unwrap(),
+        1_708_791_884_591
+    );
+}
+
+type Matrix = [[BigUint; 9]; 9];
+
+fn identity() -> Matrix {
+    let mut result: Matrix = Default::default();
+    for (i, row) in result.iter_mut().enumerate() {
+        row[i] = 1u64.into();
+    }
+    result
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let entries: Vec<BigUint> = (0..81)
+        .into_par_iter()
+        .map(|idx| {
+            let (i, j) = (idx / 9, idx % 9);
+            (0..9).map(|k| &a[i][k] * &b[k][j]).sum()
+        })
+        .collect();
+    let mut result: Matrix = Default::default();
+    for (idx, entry) in entries.into_iter().enumerate() {
+        result[idx / 9][idx % 9] = entry;
+    }
+    result
+}
+
+/// Like `count_lanternfish`, but raises the one day transition matrix to the
+/// `num_days` power by repeated squaring, so that it takes O(log num_days)
+/// matrix multiplies rather than a step per day.
+fn count_lanternfish_matexp(input: &str, num_days: u64) -> Result<BigUint> {
+    let initial = timer_histogram(input, 0)?;
+    // new[i] = sum over j of transition[i][j] * old[j]
+    let mut transition: Matrix = Default::default();
+    for i in 0..8 {
+        transition[i][i + 1] = 1u64.into();
+    }
+    transition[6][0] = 1u64.into();
+    transition[8][0] = 1u64.into();
+
+    let mut power = identity();
+    let mut remaining = num_days;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            power = multiply(&power, &transition);
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            transition = multiply(&transition, &transition);
+        }
+    }
+
+    Ok(power
+        .iter()
+        .flat_map(|row| row.iter().zip(initial.iter()).map(|(a, b)| a * b))
+        .sum())
+}
+
+#[test]
+fn test_count_lanternfish_matexp() {
+    for days in [0, 1, 18, 80, 256] {
+        assert_eq!(
+            count_lanternfish_matexp("3,4,3,1,2", days).unwrap(),
+            count_lanternfish("3,4,3,1,2", days).unwrap()
+        );
+    }
+    assert_eq!(
+        count_lanternfish_matexp(include_str!("./day6.txt"), 256).unwrap(),
+        1_708_791_884_591u64.into()
+    );
+    if cfg!(debug_assertions) {
+        return; // skip test in debug mode, it's slow when it's unoptimized
+    }
+    let big = count_lanternfish_matexp("3,4,3,1,2", 9_999_999)
+        .unwrap()
+        .to_string();
+    assert_eq!(big.len(), 378_346);
+    assert!(big.starts_with("4182599183"));
+    assert!(big.ends_with("6707352532"));
 }
 
 #[test]
@@ -1,58 +1,346 @@
 use anyhow::{anyhow, Context, Result};
 use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-fn part_1(input: &str) -> Result<i64> {
-    let vals = input
-        .split(",")
+/// Parses the comma separated crab positions, ignoring surrounding whitespace
+/// and empty entries (e.g. from a trailing newline).
+fn parse_positions(input: &str) -> Result<Vec<i64>> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
         .map(|s| {
             s.parse::<i64>()
                 .with_context(|| anyhow!("Failed to parse {}", s))
         })
-        .collect::<Result<Vec<i64>>>()?;
-    let min = *vals.iter().min().ok_or(anyhow!("Empty input"))?;
-    let max = *vals.iter().max().ok_or(anyhow!("Empty input"))?;
-    (min..=max)
+        .collect()
+}
+
+/// Finds the least total fuel to align every crab on one position, where
+/// `cost` gives the fuel for a single crab to move a given distance.
+fn min_fuel<F: Fn(i64) -> i64 + Sync>(input: &str, cost: F) -> Result<i64> {
+    Ok(cheapest_alignment(input, cost)?.1)
+}
+
+/// Returns the `(position, fuel)` that needs the least total fuel, preferring
+/// the lowest position on ties.
+fn cheapest_alignment<F: Fn(i64) -> i64 + Sync>(input: &str, cost: F) -> Result<(i64, i64)> {
+    let pairs: Vec<(i64, u64)> = parse_positions(input)?
         .into_iter()
-        .map(|target| vals.iter().map(|&val| (val - target).abs()).sum::<i64>())
+        .map(|val| (val, 1))
+        .collect();
+    cheapest_weighted_alignment(&pairs, cost)
+}
+
+/// Like `cheapest_alignment`, but for crabs given as `(position, count)`
+/// pairs.
+fn cheapest_weighted_alignment<F: Fn(i64) -> i64 + Sync>(
+    pairs: &[(i64, u64)],
+    cost: F,
+) -> Result<(i64, i64)> {
+    let min = pairs.iter().map(|&(val, _)| val).min();
+    let max = pairs.iter().map(|&(val, _)| val).max();
+    let (min, max) = min.zip(max).ok_or(anyhow!("Empty input"))?;
+    let (fuel, position) = (min..=max)
+        .into_par_iter()
+        .map(|target| {
+            let fuel = pairs
+                .par_iter()
+                .map(|&(val, count)| cost((val - target).abs()) * count as i64)
+                .sum::<i64>();
+            (fuel, target)
+        })
         .min()
-        .ok_or(anyhow!("Empty input"))
+        .ok_or(anyhow!("Empty input"))?;
+    Ok((position, fuel))
+}
+
+/// Finds the least total fuel for crabs given as `(position, count)` pairs,
+/// with the part 2 cost if `triangular` and the part 1 cost otherwise.
+fn min_fuel_weighted(pairs: &[(i64, u64)], triangular: bool) -> Result<i64> {
+    let (_, fuel) = if triangular {
+        cheapest_weighted_alignment(pairs, triangular_cost)?
+    } else {
+        cheapest_weighted_alignment(pairs, |distance| distance)?
+    };
+    Ok(fuel)
+}
+
+#[test]
+fn test_min_fuel_weighted() {
+    let example = "16,1,2,0,4,2,7,1,2,14";
+    let pairs = [(0, 1), (1, 2), (2, 3), (4, 1), (7, 1), (14, 1), (16, 1)];
+    assert_eq!(
+        min_fuel_weighted(&pairs, false).unwrap(),
+        part_1(example).unwrap()
+    );
+    assert_eq!(
+        min_fuel_weighted(&pairs, true).unwrap(),
+        part_2(example).unwrap()
+    );
+    assert!(min_fuel_weighted(&[], false).is_err());
+}
+
+/// The fuel to move `distance` steps when each step costs one more than the
+/// last, i.e. 1 + 2 + ... + distance.
+fn triangular_cost(distance: i64) -> i64 {
+    distance * (distance + 1) / 2
+}
+
+/// Returns the position the crabs should align on and the fuel it takes, with
+/// the part 2 cost if `triangular` and the part 1 cost otherwise.
+fn best_alignment(input: &str, triangular: bool) -> Result<(i64, i64)> {
+    if triangular {
+        cheapest_alignment(input, triangular_cost)
+    } else {
+        cheapest_alignment(input, |distance| distance)
+    }
+}
+
+#[test]
+fn test_best_alignment() {
+    assert_eq!(
+        best_alignment("16,1,2,0,4,2,7,1,2,14", false).unwrap(),
+        (2, 37)
+    );
+    assert_eq!(
+        best_alignment("16,1,2,0,4,2,7,1,2,14", true).unwrap(),
+        (5, 168)
+    );
+}
+
+/// Returns `(target, fuel)` for every target from the leftmost to the
+/// rightmost crab, with the part 2 cost if `triangular` and the part 1 cost
+/// otherwise.
+fn fuel_curve(input: &str, triangular: bool) -> Result<Vec<(i64, i64)>> {
+    let vals = parse_positions(input)?;
+    let min = *vals.iter().min().ok_or(anyhow!("Empty input"))?;
+    let max = *vals.iter().max().ok_or(anyhow!("Empty input"))?;
+    let cost = if triangular {
+        triangular_cost
+    } else {
+        |distance| distance
+    };
+    Ok((min..=max)
+        .into_par_iter()
+        .map(|target| {
+            let fuel = vals.iter().map(|&val| cost((val - target).abs())).sum();
+            (target, fuel)
+        })
+        .collect())
+}
+
+#[test]
+fn test_fuel_curve() {
+    let curve = fuel_curve("16,1,2,0,4,2,7,1,2,14", false).unwrap();
+    assert_eq!(curve.len(), 17);
+    assert_eq!(curve[0], (0, 49));
+    assert_eq!(curve.iter().min_by_key(|&&(_, fuel)| fuel), Some(&(2, 37)));
+    let curve = fuel_curve("16,1,2,0,4,2,7,1,2,14", true).unwrap();
+    assert_eq!(curve.iter().min_by_key(|&&(_, fuel)| fuel), Some(&(5, 168)));
+}
+
+#[test]
+fn test_min_fuel() {
+    assert_eq!(
+        min_fuel("16,1,2,0,4,2,7,1,2,14", |distance| distance * distance).unwrap(),
+        // squared distance is minimized at the mean, 4.9, so at 5
+        291
+    );
+}
+
+fn part_1(input: &str) -> Result<i64> {
+    min_fuel(input, |distance| distance)
 }
 
 #[test]
 fn test_part_1() {
     assert_eq!(part_1("16,1,2,0,4,2,7,1,2,14").unwrap(), 37);
+    assert_eq!(part_1("16,1,2,0,4,2,7,1,2,14\n").unwrap(), 37);
+    assert_eq!(part_1_median(" 16, 1,2,0,4,2,7,1,2,14,\n").unwrap(), 37);
     assert_eq!(part_1(include_str!("./day7.txt")).unwrap(), 335_271);
 }
 
+/// Like `part_1`, but in linear time. The total distance to every crab is
+/// minimized at the median, as moving away from it moves away from at least
+/// as many crabs as it moves towards.
+fn part_1_median(input: &str) -> Result<i64> {
+    median_fuel(&mut parse_positions(input)?)
+}
+
+fn median_fuel(vals: &mut [i64]) -> Result<i64> {
+    if vals.is_empty() {
+        return Err(anyhow!("Empty input"));
+    }
+    let middle = vals.len() / 2;
+    let (_, &mut median, _) = vals.select_nth_unstable(middle);
+    Ok(vals.iter().map(|&val| (val - median).abs()).sum())
+}
+
+#[test]
+fn test_part_1_median() {
+    assert_eq!(part_1_median("16,1,2,0,4,2,7,1,2,14").unwrap(), 37);
+    assert_eq!(part_1_median(include_str!("./day7.txt")).unwrap(), 335_271);
+}
+
 fn part_2(input: &str) -> Result<i64> {
-    let vals = input
-        .split(",")
-        .map(|s| {
-            s.parse::<i64>()
-                .with_context(|| anyhow!("Failed to parse {}", s))
-        })
-        .collect::<Result<Vec<i64>>>()?;
-    let min = *vals.iter().min().ok_or(anyhow!("Empty input"))?;
-    let max = *vals.iter().max().ok_or(anyhow!("Empty input"))?;
-    (min..=max)
-        .into_par_iter()
+    min_fuel(input, triangular_cost)
+}
+
+#[test]
+fn test_part_2() {
+    assert_eq!(part_2("16,1,2,0,4,2,7,1,2,14").unwrap(), 168);
+    assert_eq!(part_2(include_str!("./day7.txt")).unwrap(), 95_851_339);
+}
+
+fn triangular_fuel(vals: &[i64], target: i64) -> i64 {
+    vals.iter()
+        .map(|&val| triangular_cost((val - target).abs()))
+        .sum()
+}
+
+/// Like `part_2`, but uses a ternary search. The total fuel is convex in the
+/// target, so comparing two interior points tells us which third of the
+/// range can't contain the minimum.
+fn part_2_ternary(input: &str) -> Result<i64> {
+    ternary_fuel(&parse_positions(input)?)
+}
+
+fn ternary_fuel(vals: &[i64]) -> Result<i64> {
+    let mut lo = *vals.iter().min().ok_or(anyhow!("Empty input"))?;
+    let mut hi = *vals.iter().max().ok_or(anyhow!("Empty input"))?;
+    while hi - lo > 2 {
+        let third = (hi - lo) / 3;
+        let (m1, m2) = (lo + third, hi - third);
+        let (f1, f2) = (triangular_fuel(vals, m1), triangular_fuel(vals, m2));
+        match f1.cmp(&f2) {
+            std::cmp::Ordering::Less => hi = m2 - 1,
+            std::cmp::Ordering::Greater => lo = m1 + 1,
+            // with a convex cost, the minimum is somewhere in between
+            std::cmp::Ordering::Equal => (lo, hi) = (m1, m2),
+        }
+    }
+    (lo..=hi)
+        .map(|target| triangular_fuel(vals, target))
+        .min()
+        .ok_or(anyhow!("Empty input"))
+}
+
+/// Like `part_2`, but in linear time, by only checking targets near the mean.
+fn part_2_mean_bracket(input: &str) -> Result<i64> {
+    let vals = parse_positions(input)?;
+    if vals.is_empty() {
+        return Err(anyhow!("Empty input"));
+    }
+    // Treating the target x as continuous, each crab at p costs
+    // (d^2 + d) / 2 where d = |x - p|, so the slope of the total is
+    //   sum(x - p) + sum(sign(x - p)) / 2 = n * (x - mean) + sum(sign(x - p)) / 2
+    // The signs sum to somewhere in -n..=n, so the slope can only be zero
+    // within 1/2 of the mean. The best integer target is next to that real
+    // minimum, so it's somewhere in floor(mean - 1/2)..=ceil(mean + 1/2).
+    // That's the floor and ceil of the mean, plus one more target when the
+    // mean is within 1/2 of an integer. We'd only skip that extra target by
+    // assuming the total is symmetric about its minimum, which the |d| term
+    // breaks.
+    //
+    // In integer arithmetic, with mean = sum / n:
+    //   floor(mean - 1/2) = floor((2 * sum - n) / 2n)
+    //   ceil(mean + 1/2) = ceil((2 * sum + n) / 2n)
+    let n = vals.len() as i64;
+    let sum: i64 = vals.iter().sum();
+    let lo = (2 * sum - n).div_euclid(2 * n);
+    let hi = -(-(2 * sum + n)).div_euclid(2 * n);
+    (lo..=hi)
+        .map(|target| triangular_fuel(&vals, target))
+        .min()
+        .ok_or(anyhow!("Empty input"))
+}
+
+#[test]
+fn test_part_2_mean_bracket() {
+    assert_eq!(part_2_mean_bracket("16,1,2,0,4,2,7,1,2,14").unwrap(), 168);
+    assert_eq!(
+        part_2_mean_bracket(include_str!("./day7.txt")).unwrap(),
+        part_2(include_str!("./day7.txt")).unwrap()
+    );
+    // a lopsided input, where the real minimum isn't at the mean
+    assert_eq!(
+        part_2_mean_bracket("0,0,0,0,0,0,0,0,0,40").unwrap(),
+        min_fuel("0,0,0,0,0,0,0,0,0,40", triangular_cost).unwrap()
+    );
+}
+
+/// Like `part_2_mean_bracket`, but accumulates fuel in an `i128` so that
+/// inputs with many far apart crabs can't overflow.
+fn part_2_i128(input: &str) -> Result<i128> {
+    let vals: Vec<i128> = parse_positions(input)?
+        .into_iter()
+        .map(i128::from)
+        .collect();
+    if vals.is_empty() {
+        return Err(anyhow!("Empty input"));
+    }
+    // see part_2_mean_bracket for why these bounds hold
+    let n = vals.len() as i128;
+    let sum: i128 = vals.iter().sum();
+    let lo = (2 * sum - n).div_euclid(2 * n);
+    let hi = -(-(2 * sum + n)).div_euclid(2 * n);
+    (lo..=hi)
         .map(|target| {
-            vals.par_iter()
+            vals.iter()
                 .map(|&val| {
                     let distance = (val - target).abs();
-                    (0..=distance).sum::<i64>()
+                    distance * (distance + 1) / 2
                 })
-                .sum::<i64>()
+                .sum()
         })
         .min()
         .ok_or(anyhow!("Empty input"))
 }
 
 #[test]
-fn test_part_2() {
-    if cfg!(debug_assertions) {
-        return; // skip test in debug mode, it's slow when it's unoptimized
-    }
-    assert_eq!(part_2("16,1,2,0,4,2,7,1,2,14").unwrap(), 168);
-    assert_eq!(part_2(include_str!("./day7.txt")).unwrap(), 95_851_339);
+fn test_part_2_i128() {
+    assert_eq!(part_2_i128("16,1,2,0,4,2,7,1,2,14").unwrap(), 168);
+    // eight crabs either side of a gap of i32::MAX each need 2^60 fuel per
+    // pair to meet in the middle, which is one more than fits in an i64
+    let input = ["0,2147483647"; 8].join(",");
+    assert_eq!(part_2_i128(&input).unwrap(), 1 << 63);
+    assert!(part_2_i128(&input).unwrap() > i64::MAX as i128);
+}
+
+#[test]
+fn test_part_2_ternary() {
+    assert_eq!(part_2_ternary("16,1,2,0,4,2,7,1,2,14").unwrap(), 168);
+    assert_eq!(
+        part_2_ternary(include_str!("./day7.txt")).unwrap(),
+        95_851_339
+    );
+}
+
+/// Computes both parts, parsing the input only once.
+fn solve(input: &str) -> Result<(i64, i64)> {
+    let mut vals = parse_positions(input)?;
+    let part_2 = ternary_fuel(&vals)?;
+    let part_1 = median_fuel(&mut vals)?;
+    Ok((part_1, part_2))
+}
+
+#[test]
+fn test_solve() {
+    assert_eq!(solve("16,1,2,0,4,2,7,1,2,14").unwrap(), (37, 168));
+    assert_eq!(
+        solve(include_str!("./day7.txt")).unwrap(),
+        (335_271, 95_851_339)
+    );
+}
+
+#[test]
+fn test_negative_positions() {
+    assert_eq!(best_alignment("-5,0,5", false).unwrap(), (0, 10));
+    assert_eq!(part_1_median("-5,0,5").unwrap(), 10);
+    assert_eq!(best_alignment("-5,0,5", true).unwrap(), (0, 30));
+    assert_eq!(part_2_ternary("-5,0,5").unwrap(), 30);
+    assert_eq!(part_2_mean_bracket("-5,0,5").unwrap(), 30);
+    assert_eq!(part_2_mean_bracket("-9,-8").unwrap(), 1);
+    assert_eq!(part_2_i128("-5,0,5").unwrap(), 30);
+    assert_eq!(solve("-5,0,5").unwrap(), (10, 30));
 }
@@ -1,10 +1,11 @@
 use enum_map::{enum_map, Enum, EnumMap};
 
 use anyhow::{anyhow, Result};
+use itertools::Itertools;
 use smallvec::SmallVec;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
-enum DisplayedDigit {
+pub enum DisplayedDigit {
     Zero,
     One,
     Two,
@@ -17,6 +18,32 @@ enum DisplayedDigit {
     Nine,
 }
 impl DisplayedDigit {
+    fn every() -> [DisplayedDigit; 10] {
+        [
+            DisplayedDigit::Zero,
+            DisplayedDigit::One,
+            DisplayedDigit::Two,
+            DisplayedDigit::Three,
+            DisplayedDigit::Four,
+            DisplayedDigit::Five,
+            DisplayedDigit::Six,
+            DisplayedDigit::Seven,
+            DisplayedDigit::Eight,
+            DisplayedDigit::Nine,
+        ]
+    }
+
+    /// The digit displayed by exactly these segments, which must be sorted.
+    fn from_segments(segments: &[Segment]) -> Option<DisplayedDigit> {
+        DisplayedDigit::every()
+            .into_iter()
+            .find(|digit| digit.segments() == segments)
+    }
+
+    fn value(&self) -> u32 {
+        *self as u32
+    }
+
     fn segments(&self) -> &'static [Segment] {
         match self {
             DisplayedDigit::One => &[Segment::C, Segment::F],
@@ -62,8 +89,20 @@ impl DisplayedDigit {
     }
 }
 
+#[test]
+fn test_from_segments() {
+    for digit in DisplayedDigit::every() {
+        assert_eq!(DisplayedDigit::from_segments(digit.segments()), Some(digit));
+    }
+    assert_eq!(
+        DisplayedDigit::from_segments(&[Segment::A, Segment::B]),
+        None
+    );
+    assert_eq!(DisplayedDigit::from_segments(&[]), None);
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Enum)]
-enum Segment {
+pub enum Segment {
     A,
     B,
     C,
@@ -86,7 +125,7 @@ impl Segment {
     }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Enum)]
-enum Wire {
+pub enum Wire {
     A,
     B,
     C,
@@ -114,108 +153,524 @@ impl TryFrom<u8> for Wire {
 
     fn try_from(value: u8) -> Result<Self> {
         match value {
-            b'a' => Ok(Wire::A),
-            b'b' => Ok(Wire::B),
-            b'c' => Ok(Wire::C),
-            b'd' => Ok(Wire::D),
-            b'e' => Ok(Wire::E),
-            b'f' => Ok(Wire::F),
-            b'g' => Ok(Wire::G),
+            b'a' | b'A' => Ok(Wire::A),
+            b'b' | b'B' => Ok(Wire::B),
+            b'c' | b'C' => Ok(Wire::C),
+            b'd' | b'D' => Ok(Wire::D),
+            b'e' | b'E' => Ok(Wire::E),
+            b'f' | b'F' => Ok(Wire::F),
+            b'g' | b'G' => Ok(Wire::G),
             _ => Err(anyhow!("Invalid wire identifier {}", value)),
         }
     }
 }
 
+#[test]
+fn test_uppercase_wires() {
+    assert_eq!(
+        parse_words("ACEDGFB").unwrap(),
+        parse_words("acedgfb").unwrap()
+    );
+    assert!(Wire::try_from(b'H').is_err());
+}
+
+/// The segments each wire could still be connected to, as a bitmask with
+/// bit `n` set for the `n`th segment.
 struct SegmentMapping {
-    mapping: EnumMap<Wire, SmallVec<[Segment; 7]>>,
+    mapping: EnumMap<Wire, u8>,
 }
 impl SegmentMapping {
+    const ALL_SEGMENTS: u8 = 0b111_1111;
+
     fn new() -> Self {
-        let mapping = enum_map! {
-            Wire::A => SmallVec::from(Segment::every()),
-            Wire::B => SmallVec::from(Segment::every()),
-            Wire::C => SmallVec::from(Segment::every()),
-            Wire::D => SmallVec::from(Segment::every()),
-            Wire::E => SmallVec::from(Segment::every()),
-            Wire::F => SmallVec::from(Segment::every()),
-            Wire::G => SmallVec::from(Segment::every()),
-        };
-        SegmentMapping { mapping }
+        SegmentMapping {
+            mapping: enum_map! { _ => Self::ALL_SEGMENTS },
+        }
     }
 
-    fn constrain(&mut self, wire: Wire, options: &[Segment]) {
-        self.mapping[wire].retain(|segment| options.contains(segment));
+    fn mask(segments: &[Segment]) -> u8 {
+        segments
+            .iter()
+            .fold(0, |mask, &segment| mask | 1 << segment as u8)
     }
-}
 
-struct Problem {}
-impl Problem {
-    fn initial() -> Self {
-        Self {}
+    /// The segments that `wire` could still be connected to, in order.
+    fn options(&self, wire: Wire) -> SmallVec<[Segment; 7]> {
+        Segment::every()
+            .into_iter()
+            .filter(|&segment| self.mapping[wire] & Self::mask(&[segment]) != 0)
+            .collect()
     }
 
-    fn analyze_signals(line: &str) -> Result<()> {
-        let (wire_patterns, _message) = line
-            .split_once(" | ")
-            .ok_or_else(|| anyhow!("Line missing | character: {:?}", line))?;
-        let mut _problem = Self::initial();
-        let wire_patterns = wire_patterns
-            .split(" ")
-            .map(|wire_pattern| {
-                wire_pattern
-                    .as_bytes()
-                    .iter()
-                    .map(|&v| v.try_into())
-                    .collect::<Result<Vec<Wire>>>()
+    /// Limits `wire` to the given segments. If that pins a wire down to one
+    /// segment, no other wire can be connected to that segment.
+    ///
+    /// Errors if that leaves any wire without a possible segment.
+    fn constrain(&mut self, wire: Wire, options: &[Segment]) -> Result<()> {
+        self.constrain_mask(wire, Self::mask(options))
+    }
+
+    fn constrain_mask(&mut self, wire: Wire, mask: u8) -> Result<()> {
+        self.mapping[wire] &= mask;
+        match self.mapping[wire].count_ones() {
+            0 => {
+                return Err(anyhow!(
+                    "Contradictory constraints: wire {:?} can't connect to any segment",
+                    wire
+                ))
+            }
+            1 => {
+                let segment = self.mapping[wire];
+                for other in Wire::every() {
+                    if other != wire && self.mapping[other] & segment != 0 {
+                        self.constrain_mask(other, !segment)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether every wire is known to connect to exactly one segment.
+    fn is_solved(&self) -> bool {
+        self.mapping.values().all(|mask| mask.count_ones() == 1)
+    }
+
+    fn resolve(&self) -> Option<EnumMap<Wire, Segment>> {
+        if !self.is_solved() {
+            return None;
+        }
+        let segments = Segment::every();
+        Some(enum_map! { wire => segments[self.mapping[wire].trailing_zeros() as usize] })
+    }
+}
+
+#[test]
+fn test_bitmask_mapping() {
+    let mut mapping = SegmentMapping::new();
+    assert_eq!(mapping.options(Wire::A).as_slice(), &Segment::every());
+    mapping
+        .constrain(Wire::A, &[Segment::B, Segment::G])
+        .unwrap();
+    assert_eq!(mapping.mapping[Wire::A], 0b100_0010);
+    assert_eq!(
+        mapping.options(Wire::A).as_slice(),
+        &[Segment::B, Segment::G]
+    );
+
+    let values: Vec<u32> = EXAMPLE
+        .trim()
+        .lines()
+        .map(|line| decode_line(line).unwrap())
+        .collect();
+    assert_eq!(
+        values,
+        [8394, 9781, 1197, 9361, 4873, 8418, 4548, 1625, 8717, 4315]
+    );
+}
+
+#[test]
+fn test_constraint_propagation() {
+    let mut mapping = SegmentMapping::new();
+    mapping
+        .constrain(Wire::A, &[Segment::C, Segment::F])
+        .unwrap();
+    mapping
+        .constrain(Wire::B, &[Segment::C, Segment::F])
+        .unwrap();
+    mapping
+        .constrain(Wire::C, &[Segment::A, Segment::C, Segment::F])
+        .unwrap();
+    // A and B must use up C and F, but we don't track pairs
+    assert_eq!(
+        mapping.options(Wire::C).as_slice(),
+        &[Segment::A, Segment::C, Segment::F]
+    );
+    mapping.constrain(Wire::A, &[Segment::C]).unwrap();
+    assert_eq!(mapping.options(Wire::B).as_slice(), &[Segment::F]);
+    assert_eq!(mapping.options(Wire::C).as_slice(), &[Segment::A]);
+    assert!(!mapping.options(Wire::D).contains(&Segment::A));
+    assert!(!mapping.is_solved());
+    assert_eq!(mapping.resolve(), None);
+}
+
+/// Works out which segment each wire is connected to from the line's ten
+/// patterns.
+fn analyze_signals(line: &str) -> Result<EnumMap<Wire, Segment>> {
+    let (wire_patterns, _message) = line
+        .split_once(" | ")
+        .ok_or_else(|| anyhow!("Line missing | character: {:?}", line))?;
+    let wire_patterns = parse_words(wire_patterns)?;
+    deduce(&wire_patterns)?
+        .resolve()
+        .ok_or_else(|| anyhow!("Couldn't work out the wiring for {:?}", line))
+}
+
+/// Narrows down which segments each wire could be connected to, using the
+/// ten patterns.
+fn deduce(wire_patterns: &[Vec<Wire>]) -> Result<SegmentMapping> {
+    let mut mapping = SegmentMapping::new();
+    let find_pattern = |digit: DisplayedDigit| {
+        let len = digit.segments().len();
+        wire_patterns
+            .iter()
+            .find(|wire_pattern| wire_pattern.len() == len)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Missing the pattern with {} wires, for the digit {}",
+                    len,
+                    digit.value()
+                )
             })
-            .collect::<Result<Vec<Vec<Wire>>>>()?;
-        let mut mapping = SegmentMapping::new();
-        let one_pattern = wire_patterns
+    };
+    let one_pattern = find_pattern(DisplayedDigit::One)?;
+    let seven_pattern = find_pattern(DisplayedDigit::Seven)?;
+    let four_pattern = find_pattern(DisplayedDigit::Four)?;
+    for &wire in one_pattern {
+        mapping.constrain(wire, DisplayedDigit::One.segments())?;
+    }
+    for &wire in seven_pattern {
+        mapping.constrain(wire, DisplayedDigit::Seven.segments())?;
+    }
+    let a_wire = seven_pattern
+        .iter()
+        .find(|w| !one_pattern.contains(w))
+        .ok_or_else(|| anyhow!("The 7 pattern doesn't contain the 1 pattern"))?;
+    mapping.constrain(*a_wire, &[Segment::A])?;
+    for &wire in four_pattern {
+        mapping.constrain(wire, DisplayedDigit::Four.segments())?;
+    }
+    let bd_wires = four_pattern
+        .iter()
+        .filter(|w| !one_pattern.contains(w))
+        .collect::<Vec<&Wire>>();
+    for &&wire in bd_wires.iter() {
+        mapping.constrain(wire, &[Segment::B, Segment::D])?;
+    }
+    // The wires in none of 1, 4, or 7 are E and G.
+    for wire in Wire::every() {
+        let in_easy_pattern = [one_pattern, seven_pattern, four_pattern]
             .iter()
-            .filter(|wire_pattern| wire_pattern.len() == 2)
-            .next();
-        let seven_pattern = wire_patterns.iter().filter(|w| w.len() == 3).next();
-        let four_pattern = wire_patterns.iter().filter(|w| w.len() == 4).next();
-        if let Some(one_pattern) = one_pattern {
-            for &wire in one_pattern {
-                mapping.constrain(wire, DisplayedDigit::One.segments());
-            }
+            .any(|pattern| pattern.contains(&wire));
+        if !in_easy_pattern {
+            mapping.constrain(wire, &[Segment::E, Segment::G])?;
         }
-        if let Some(seven_pattern) = seven_pattern {
-            for &wire in seven_pattern {
-                mapping.constrain(wire, DisplayedDigit::Seven.segments());
-            }
-            if let Some(one_pattern) = one_pattern {
-                let a_wire = seven_pattern
-                    .iter()
-                    .filter(|w| !one_pattern.contains(w))
-                    .next()
-                    .unwrap();
-                mapping.constrain(*a_wire, &[Segment::A]);
+    }
+    // 0, 6, and 9 are each missing one of C, D, and E, and share the rest.
+    let six_patterns: Vec<&Vec<Wire>> = wire_patterns.iter().filter(|w| w.len() == 6).collect();
+    if six_patterns.len() == 3 {
+        for wire in Wire::every() {
+            if six_patterns.iter().all(|pattern| pattern.contains(&wire)) {
+                mapping.constrain(wire, &[Segment::A, Segment::B, Segment::F, Segment::G])?;
+            } else {
+                mapping.constrain(wire, &[Segment::C, Segment::D, Segment::E])?;
             }
         }
-        if let Some(four_pattern) = four_pattern {
-            for &wire in four_pattern {
-                mapping.constrain(wire, DisplayedDigit::Four.segments());
-            }
-            if let Some(one_pattern) = one_pattern {
-                let bd_wires = four_pattern
-                    .iter()
-                    .filter(|w| !one_pattern.contains(w))
-                    .collect::<Vec<&Wire>>();
-                for &&wire in bd_wires.iter() {
-                    mapping.constrain(wire, &[Segment::B, Segment::D]);
-                }
+    }
+    // 2, 3, and 5 all share A, D, and G.
+    let five_patterns: Vec<&Vec<Wire>> = wire_patterns.iter().filter(|w| w.len() == 5).collect();
+    if five_patterns.len() == 3 {
+        for wire in Wire::every() {
+            if five_patterns.iter().all(|pattern| pattern.contains(&wire)) {
+                mapping.constrain(wire, &[Segment::A, Segment::D, Segment::G])?;
+            } else {
+                mapping.constrain(wire, &[Segment::B, Segment::C, Segment::E, Segment::F])?;
             }
         }
+    }
 
-        Ok(())
+    Ok(mapping)
+}
+
+/// Parses space separated words of wire letters.
+fn parse_words(words: &str) -> Result<Vec<Vec<Wire>>> {
+    words
+        .split_whitespace()
+        .map(|word| {
+            word.as_bytes()
+                .iter()
+                .map(|&v| v.try_into())
+                .collect::<Result<Vec<Wire>>>()
+        })
+        .collect()
+}
+
+/// Works out the wiring from the line's patterns, and uses it to read the
+/// four digit output value.
+fn decode_line(line: &str) -> Result<u32> {
+    let mapping = analyze_signals(line)?;
+    read_value(&parse_output(line)?, &mapping)
+}
+
+/// Parses the four output words that come after the ` | ` in a line.
+fn parse_output(line: &str) -> Result<Vec<Vec<Wire>>> {
+    let (_patterns, output) = line
+        .split_once(" | ")
+        .ok_or_else(|| anyhow!("Line missing | character: {:?}", line))?;
+    let words = parse_words(output)?;
+    if words.len() != 4 {
+        return Err(anyhow!(
+            "Expected 4 output words but found {} in {:?}",
+            words.len(),
+            line
+        ));
     }
+    Ok(words)
 }
 
+#[test]
+fn test_parse_output() {
+    let easy =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    let output = parse_output(easy).unwrap();
+    assert_eq!(output.len(), 4);
+    assert_eq!(output[0], vec![Wire::C, Wire::D, Wire::F, Wire::E, Wire::B]);
+    assert!(parse_output("ab | ab abc").is_err());
+    assert!(parse_output("ab ab abc abcd ab").is_err());
+}
+
+/// The digit that a word of wires displays with the given wiring, if any.
+fn read_digit(word: &[Wire], mapping: &EnumMap<Wire, Segment>) -> Option<DisplayedDigit> {
+    let mut segments: Vec<Segment> = word.iter().map(|&wire| mapping[wire]).collect();
+    segments.sort();
+    DisplayedDigit::from_segments(&segments)
+}
+
+/// Decodes a single word of wire letters with a solved wiring.
+pub fn decode_word(word: &str, mapping: &EnumMap<Wire, Segment>) -> Result<DisplayedDigit> {
+    let wires = word
+        .as_bytes()
+        .iter()
+        .map(|&v| v.try_into())
+        .collect::<Result<Vec<Wire>>>()?;
+    read_digit(&wires, mapping).ok_or_else(|| anyhow!("{:?} isn't a digit", word))
+}
+
+#[test]
+fn test_decode_word() {
+    let easy =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    let mapping = analyze_signals(easy).unwrap();
+    assert_eq!(
+        decode_word("cagedb", &mapping).unwrap(),
+        DisplayedDigit::Zero
+    );
+    assert_eq!(decode_word("ab", &mapping).unwrap(), DisplayedDigit::One);
+    assert_eq!(
+        decode_word("acedgfb", &mapping).unwrap(),
+        DisplayedDigit::Eight
+    );
+    assert!(decode_word("abc", &mapping).is_err());
+    assert!(decode_word("xyz", &mapping).is_err());
+}
+
+/// Describes a solved wiring, e.g. `a->c b->f ...`, followed by the wires
+/// that light up each digit.
+fn render_mapping(mapping: &EnumMap<Wire, Segment>) -> String {
+    let letter = |index: usize| (b'a' + index as u8) as char;
+    let mut rendered = mapping
+        .iter()
+        .map(|(wire, &segment)| format!("{}->{}", letter(wire as usize), letter(segment as usize)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    for digit in DisplayedDigit::every() {
+        let wires: String = Wire::every()
+            .into_iter()
+            .filter(|&wire| digit.segments().contains(&mapping[wire]))
+            .map(|wire| letter(wire as usize))
+            .collect();
+        rendered.push_str(&format!("\n{}: {}", digit.value(), wires));
+    }
+    rendered
+}
+
+#[test]
+fn test_render_mapping() {
+    let easy =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    let rendered = render_mapping(&analyze_signals(easy).unwrap());
+    for wire in "abcdefg".chars() {
+        assert!(rendered.contains(&format!("{}->", wire)), "{}", rendered);
+    }
+    assert_eq!(rendered.matches("->").count(), 7);
+    assert!(rendered.starts_with("a->c b->f "), "{}", rendered);
+    assert!(rendered.contains("\n0: abcdeg\n"), "{}", rendered);
+    assert!(rendered.contains("\n1: ab\n"), "{}", rendered);
+}
+
+/// Reads the number displayed by `words`, most significant digit first.
+fn read_value(words: &[Vec<Wire>], mapping: &EnumMap<Wire, Segment>) -> Result<u32> {
+    let mut value = 0;
+    for word in words {
+        let digit =
+            read_digit(word, mapping).ok_or_else(|| anyhow!("Wires {:?} aren't a digit", word))?;
+        value = value * 10 + digit.value();
+    }
+    Ok(value)
+}
+
+/// Like `decode_line`, but finds the wiring by trying every possible one
+/// until all ten patterns are valid digits.
+fn decode_line_bruteforce(line: &str) -> Result<u32> {
+    let (patterns, _output) = line
+        .split_once(" | ")
+        .ok_or_else(|| anyhow!("Line missing | character: {:?}", line))?;
+    let patterns = parse_words(patterns)?;
+    let output = parse_output(line)?;
+    for segments in Segment::every().into_iter().permutations(7) {
+        let mapping = enum_map! { wire => segments[wire as usize] };
+        if patterns
+            .iter()
+            .all(|pattern| read_digit(pattern, &mapping).is_some())
+        {
+            return read_value(&output, &mapping);
+        }
+    }
+    Err(anyhow!("No wiring explains the patterns in {:?}", line))
+}
+
+/// Decodes the four digit output value of every line.
+fn decode_all(input: &str) -> Result<Vec<u32>> {
+    input.lines().map(decode_line).collect()
+}
+
+#[test]
+fn test_decode_all() {
+    let values = decode_all(EXAMPLE.trim()).unwrap();
+    assert_eq!(values.len(), 10);
+    assert_eq!(values[0], 8394);
+    assert_eq!(values.iter().sum::<u32>(), 61229);
+}
+
+fn part_2(input: &str) -> Result<u64> {
+    Ok(decode_all(input)?.into_iter().map(u64::from).sum())
+}
+
+/// Counts the output digits that can be identified by their number of
+/// segments alone: 1, 4, 7, and 8.
+fn part_1(input: &str) -> Result<u64> {
+    let mut count = 0;
+    for line in input.lines() {
+        let (_patterns, output) = line
+            .split_once(" | ")
+            .ok_or_else(|| anyhow!("Line missing | character: {:?}", line))?;
+        count += output
+            .split_whitespace()
+            .filter(|word| matches!(word.len(), 2 | 3 | 4 | 7))
+            .count() as u64;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce";
+
 #[test]
 fn test_part_1() {
     let easy =
         "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
-    Problem::analyze_signals(easy).unwrap();
+    analyze_signals(easy).unwrap();
+    assert_eq!(part_1(easy).unwrap(), 0);
+    assert_eq!(part_1(EXAMPLE.trim()).unwrap(), 26);
+    assert_eq!(part_1(include_str!("day8.txt")).unwrap(), 479);
+}
+
+#[test]
+fn test_analyze_signals() {
+    let easy =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    let mapping = analyze_signals(easy).unwrap();
+    let (patterns, _) = easy.split_once(" | ").unwrap();
+    let mut digits: Vec<u32> = parse_words(patterns)
+        .unwrap()
+        .iter()
+        .map(|pattern| read_digit(pattern, &mapping).unwrap().value())
+        .collect();
+    digits.sort_unstable();
+    assert_eq!(digits, (0..10).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_contradictory_constraints() {
+    let mut mapping = SegmentMapping::new();
+    mapping
+        .constrain(Wire::A, &[Segment::C, Segment::F])
+        .unwrap();
+    let err = mapping.constrain(Wire::A, &[Segment::A]).unwrap_err();
+    assert!(err.to_string().contains("wire A"), "{}", err);
+
+    // once A is pinned to C, B can't be connected to C
+    let mut mapping = SegmentMapping::new();
+    mapping.constrain(Wire::A, &[Segment::C]).unwrap();
+    let err = mapping.constrain(Wire::B, &[Segment::C]).unwrap_err();
+    assert!(err.to_string().contains("wire B"), "{}", err);
+
+    assert!(deduce(&parse_words("ab abc abcd").unwrap()).is_err());
+}
+
+#[test]
+fn test_missing_pattern() {
+    // the easy example, without "dab"
+    let line = "acedgfb cdfbe gcdfa fbcad cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    let err = decode_line(line).unwrap_err();
+    assert!(err.to_string().contains("3 wires"), "{}", err);
+}
+
+#[test]
+fn test_deduce() {
+    for line in EXAMPLE.trim().lines() {
+        let (patterns, _) = line.split_once(" | ").unwrap();
+        let mapping = deduce(&parse_words(patterns).unwrap()).unwrap();
+        assert!(mapping.is_solved());
+        assert!(mapping.resolve().is_some());
+    }
+}
+
+#[test]
+fn test_part_2() {
+    let easy =
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+    assert_eq!(decode_line(easy).unwrap(), 5353);
+    assert_eq!(part_2(EXAMPLE.trim()).unwrap(), 61229);
+    assert_eq!(part_2(include_str!("day8.txt")).unwrap(), 1_041_746);
+}
+
+#[test]
+fn test_decode_line_bruteforce() {
+    for line in EXAMPLE.trim().lines() {
+        assert_eq!(
+            decode_line_bruteforce(line).unwrap(),
+            decode_line(line).unwrap()
+        );
+    }
+    assert!(decode_line_bruteforce("ab abc abcd | ab").is_err());
+}
+
+/// Computes the part 2 sum with both the constraint solver and the brute
+/// force solver, so they can be checked against each other and timed.
+fn compare_solvers(input: &str) -> Result<(u64, u64)> {
+    let constrained = part_2(input)?;
+    let brute_force = input
+        .lines()
+        .map(|line| Ok(decode_line_bruteforce(line)? as u64))
+        .sum::<Result<u64>>()?;
+    Ok((constrained, brute_force))
+}
+
+#[test]
+fn test_compare_solvers() {
+    assert_eq!(compare_solvers(EXAMPLE.trim()).unwrap(), (61229, 61229));
+    let (constrained, brute_force) = compare_solvers(include_str!("day8.txt")).unwrap();
+    assert_eq!(constrained, brute_force);
 }
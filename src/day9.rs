@@ -1,11 +1,18 @@
-use anyhow::Result;
+use std::str::FromStr;
+
+use crate::grid::parse_digit_grid;
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
 use smallvec::SmallVec;
 
-fn neighbors(grid: &Vec<Vec<u8>>) -> Vec<(u8, (usize, usize), SmallVec<[u8; 4]>)> {
+/// A cell's height and location, with the heights of its neighbors.
+type CellNeighbors = (u8, (usize, usize), SmallVec<[u8; 4]>);
+
+fn neighbors(grid: &[Vec<u8>]) -> Vec<CellNeighbors> {
     grid.iter()
         .enumerate()
         .flat_map(|(y, line)| {
-            line.iter().enumerate().filter_map(move |(x, &v)| {
+            line.iter().enumerate().map(move |(x, &v)| {
                 let mut neighbors = SmallVec::<[u8; 4]>::new();
                 if y > 0 {
                     neighbors.push(grid[y - 1][x]);
@@ -19,13 +26,15 @@ fn neighbors(grid: &Vec<Vec<u8>>) -> Vec<(u8, (usize, usize), SmallVec<[u8; 4]>)
                 if x < line.len() - 1 {
                     neighbors.push(grid[y][x + 1]);
                 }
-                Some((v, (y, x), neighbors))
+                (v, (y, x), neighbors)
             })
         })
         .collect()
 }
 
-fn neighbors_of(grid: &Vec<Vec<u8>>, (x, y): (usize, usize)) -> SmallVec<[u8; 4]> {
+/// The heights next to `(y, x)`, which is ordered the same way as the
+/// locations from `neighbors`, with the outer index first.
+fn neighbors_of(grid: &[Vec<u8>], (y, x): (usize, usize)) -> SmallVec<[u8; 4]> {
     let mut neighbors = SmallVec::<[u8; 4]>::new();
     let line = &grid[y];
     if y > 0 {
@@ -43,7 +52,22 @@ fn neighbors_of(grid: &Vec<Vec<u8>>, (x, y): (usize, usize)) -> SmallVec<[u8; 4]
     neighbors
 }
 
-fn minima(grid: &Vec<Vec<u8>>) -> Vec<(u8, (usize, usize))> {
+#[test]
+fn test_neighbors_of() {
+    let grid = parse_digit_grid("0123\n4567\n8901").unwrap();
+    let sorted = |mut neighbors: SmallVec<[u8; 4]>| {
+        neighbors.sort_unstable();
+        neighbors.into_vec()
+    };
+    assert_eq!(sorted(neighbors_of(&grid, (0, 3))), vec![2, 7]);
+    assert_eq!(sorted(neighbors_of(&grid, (2, 0))), vec![4, 9]);
+    assert_eq!(sorted(neighbors_of(&grid, (1, 2))), vec![0, 2, 5, 7]);
+    for (_, loc, neighbors) in neighbors(&grid) {
+        assert_eq!(sorted(neighbors_of(&grid, loc)), sorted(neighbors));
+    }
+}
+
+fn minima(grid: &[Vec<u8>]) -> Vec<(u8, (usize, usize))> {
     neighbors(grid)
         .into_iter()
         .filter_map(|(val, loc, neighbors)| {
@@ -56,19 +80,133 @@ fn minima(grid: &Vec<Vec<u8>>) -> Vec<(u8, (usize, usize))> {
         .collect()
 }
 
-fn part_1(input: &str) -> Result<u64> {
-    let grid = input
-        .lines()
-        .map(|s| {
-            s.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect::<Vec<_>>()
+/// Like `minima`, but searches the rows in parallel.
+fn minima_par(grid: &[Vec<u8>]) -> Vec<(u8, (usize, usize))> {
+    grid.par_iter()
+        .enumerate()
+        .flat_map_iter(|(y, line)| {
+            line.iter().enumerate().filter_map(move |(x, &val)| {
+                if neighbors_of(grid, (y, x))
+                    .into_iter()
+                    .all(|neighbor| neighbor > val)
+                {
+                    Some((val, (y, x)))
+                } else {
+                    None
+                }
+            })
         })
-        .collect::<Vec<_>>();
-    Ok(minima(&grid)
-        .into_iter()
-        .map(|(val, _)| 1 + val as u64)
-        .sum())
+        .collect()
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678";
+
+#[test]
+fn test_minima_par() {
+    for input in [EXAMPLE, include_str!("./day9.txt")] {
+        let grid = parse_digit_grid(input).unwrap();
+        let mut sequential = minima(&grid);
+        let mut parallel = minima_par(&grid);
+        sequential.sort_unstable();
+        parallel.sort_unstable();
+        assert_eq!(parallel, sequential);
+    }
+}
+
+/// Like `minima`, but a low point must also be lower than its diagonal
+/// neighbors.
+fn minima_8(grid: &[Vec<u8>]) -> Vec<(u8, (usize, usize))> {
+    let mut minima = vec![];
+    for (y, line) in grid.iter().enumerate() {
+        for (x, &val) in line.iter().enumerate() {
+            let is_minimum = (y.saturating_sub(1)..=y + 1)
+                .flat_map(|ny| (x.saturating_sub(1)..=x + 1).map(move |nx| (ny, nx)))
+                .filter(|&neighbor| neighbor != (y, x))
+                .filter_map(|(ny, nx)| grid.get(ny)?.get(nx))
+                .all(|&neighbor| neighbor > val);
+            if is_minimum {
+                minima.push((val, (y, x)));
+            }
+        }
+    }
+    minima
+}
+
+/// A parsed heightmap, so that both parts can share the parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Heightmap {
+    cells: Vec<Vec<u8>>,
+}
+
+impl FromStr for Heightmap {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let cells = parse_digit_grid(input)?;
+        if cells.is_empty() {
+            return Err(anyhow!("Empty heightmap"));
+        }
+        Ok(Heightmap { cells })
+    }
+}
+
+impl Heightmap {
+    fn minima(&self) -> Vec<(u8, (usize, usize))> {
+        minima(&self.cells)
+    }
+
+    fn basin_size(&self, start: (usize, usize)) -> usize {
+        basin_size(&self.cells, start, 9)
+    }
+
+    /// The sum of the risk levels of the low points.
+    fn part_1(&self) -> u64 {
+        self.minima()
+            .into_iter()
+            .map(|(val, _)| 1 + val as u64)
+            .sum()
+    }
+
+    /// The product of the sizes of the three largest basins.
+    fn part_2(&self) -> u64 {
+        self.part_2_with_wall(9)
+    }
+
+    /// Like `part_2`, but with basins bounded by heights of `wall` or more,
+    /// rather than only by 9s. Any height at or above `wall` is impassable,
+    /// not just heights equal to it.
+    fn part_2_with_wall(&self, wall: u8) -> u64 {
+        let mut basin_sizes = self
+            .minima()
+            .iter()
+            .map(|&(_, loc)| basin_size(&self.cells, loc, wall) as u64)
+            .collect::<Vec<_>>();
+        basin_sizes.sort_unstable();
+
+        basin_sizes.into_iter().rev().take(3).product()
+    }
+}
+
+#[test]
+fn test_heightmap() {
+    let heightmap: Heightmap = EXAMPLE.parse().unwrap();
+    assert_eq!(heightmap.minima().len(), 4);
+    assert_eq!(heightmap.basin_size((0, 0)), 3);
+    assert_eq!(heightmap.part_1(), 15);
+    assert_eq!(heightmap.part_2(), 1134);
+
+    let err = "219\n39\n985".parse::<Heightmap>().unwrap_err();
+    assert!(err.to_string().contains("ragged grid"), "{}", err);
+    assert!("".parse::<Heightmap>().is_err());
+}
+
+fn part_1(input: &str) -> Result<u64> {
+    Ok(input.parse::<Heightmap>()?.part_1())
 }
 
 #[test]
@@ -84,12 +222,38 @@ fn test_part_1() {
     assert_eq!(part_1(include_str!("./day9.txt")).unwrap(), 486);
 }
 
-fn basin_size(grid: &Vec<Vec<u8>>, start: (usize, usize)) -> usize {
-    let mut visited = vec![vec![false; grid[0].len()]; grid.len()];
+/// Like `part_1`, but with diagonal neighbors.
+fn part_1_8(input: &str) -> Result<u64> {
+    let grid = parse_digit_grid(input)?;
+    Ok(minima_8(&grid)
+        .into_iter()
+        .map(|(val, _)| 1 + val as u64)
+        .sum())
+}
+
+#[test]
+fn test_part_1_8() {
+    // every low point in the example is also lower than its diagonals
+    let grid = parse_digit_grid(EXAMPLE).unwrap();
+    assert_eq!(minima_8(&grid), minima(&grid));
+    assert_eq!(part_1_8(EXAMPLE).unwrap(), 15);
+
+    // but here the two 0s only touch diagonally
+    let grid = parse_digit_grid("10\n01").unwrap();
+    assert_eq!(minima(&grid), vec![(0, (0, 1)), (0, (1, 0))]);
+    assert_eq!(minima_8(&grid), vec![]);
+    assert_eq!(part_1("10\n01").unwrap(), 2);
+    assert_eq!(part_1_8("10\n01").unwrap(), 0);
+}
+
+/// The number of cells reachable from `start` without crossing a height of
+/// `wall` or more.
+fn basin_size(grid: &[Vec<u8>], start: (usize, usize), wall: u8) -> usize {
+    let mut visited = vec![vec![false; grid.first().map_or(0, Vec::len)]; grid.len()];
     let mut queue = vec![start];
     let mut size = 0;
     while let Some(loc) = queue.pop() {
-        if visited[loc.0][loc.1] {
+        if grid[loc.0][loc.1] >= wall || visited[loc.0][loc.1] {
             continue;
         }
         visited[loc.0][loc.1] = true;
@@ -105,7 +269,7 @@ fn basin_size(grid: &Vec<Vec<u8>>, start: (usize, usize)) -> usize {
                 && neighbor.0 < grid.len() as i64
                 && neighbor.1 >= 0
                 && neighbor.1 < grid[0].len() as i64
-                && grid[neighbor.0 as usize][neighbor.1 as usize] != 9
+                && grid[neighbor.0 as usize][neighbor.1 as usize] < wall
             {
                 queue.push((neighbor.0 as usize, neighbor.1 as usize));
             }
@@ -114,26 +278,111 @@ fn basin_size(grid: &Vec<Vec<u8>>, start: (usize, usize)) -> usize {
     size
 }
 
+/// Labels each cell with the index in `minima` of the low point whose basin
+/// it's in, or `None` for the walls between basins, which are the cells with
+/// a height of `wall` or more, as in `basin_size`.
+fn basin_labels(grid: &[Vec<u8>], wall: u8) -> Vec<Vec<Option<usize>>> {
+    let mut labels = vec![vec![None; grid.first().map_or(0, Vec::len)]; grid.len()];
+    for (label, (_, start)) in minima(grid).into_iter().enumerate() {
+        let mut queue = vec![start];
+        while let Some((y, x)) = queue.pop() {
+            if grid[y][x] >= wall || labels[y][x].is_some() {
+                continue;
+            }
+            labels[y][x] = Some(label);
+            if y > 0 {
+                queue.push((y - 1, x));
+            }
+            if y < grid.len() - 1 {
+                queue.push((y + 1, x));
+            }
+            if x > 0 {
+                queue.push((y, x - 1));
+            }
+            if x < grid[y].len() - 1 {
+                queue.push((y, x + 1));
+            }
+        }
+    }
+    labels
+}
+
+#[test]
+fn test_basin_labels() {
+    let grid = parse_digit_grid(EXAMPLE).unwrap();
+    let labels = basin_labels(&grid, 9);
+    let mut sizes = vec![0; minima(&grid).len()];
+    for (y, row) in labels.iter().enumerate() {
+        for (x, &label) in row.iter().enumerate() {
+            match label {
+                Some(label) => sizes[label] += 1,
+                None => assert_eq!(grid[y][x], 9),
+            }
+        }
+    }
+    assert_eq!(sizes.len(), 4);
+    assert!(sizes.iter().all(|&size| size > 0));
+    sizes.sort_unstable();
+    assert_eq!(sizes[1..], [9, 9, 14]);
+    assert_eq!(labels[0][0], labels[1][0]);
+    assert_eq!(labels[0][2], None);
+
+    // with lower walls, the labeled basins match basin_size
+    let labels = basin_labels(&grid, 8);
+    for (label, &(_, loc)) in minima(&grid).iter().enumerate() {
+        let size = labels
+            .iter()
+            .flatten()
+            .filter(|&&l| l == Some(label))
+            .count();
+        assert_eq!(size, basin_size(&grid, loc, 8));
+    }
+    assert!(basin_labels(&[], 9).is_empty());
+
+    // a low point that's itself a wall isn't in any basin
+    let grid = parse_digit_grid("999\n989\n999").unwrap();
+    assert_eq!(minima(&grid), vec![(8, (1, 1))]);
+    assert_eq!(basin_size(&grid, (1, 1), 8), 0);
+    assert_eq!(basin_labels(&grid, 8)[1][1], None);
+    assert_eq!(basin_size(&grid, (1, 1), 9), 1);
+    assert_eq!(basin_labels(&grid, 9)[1][1], Some(0));
+}
+
 fn part_2(input: &str) -> Result<u64> {
-    let grid = input
-        .lines()
-        .map(|s| {
-            s.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-    let minima = minima(&grid);
-    let mut basin_sizes = minima
+    Ok(input.parse::<Heightmap>()?.part_2())
+}
+
+/// Like `part_2`, but treats every height of `wall` or more as impassable.
+/// With `wall = 9` this is the puzzle's rule.
+fn part_2_with_wall(input: &str, wall: u8) -> Result<u64> {
+    Ok(input.parse::<Heightmap>()?.part_2_with_wall(wall))
+}
+
+#[test]
+fn test_part_2_with_wall() {
+    assert_eq!(part_2_with_wall(EXAMPLE, 9).unwrap(), 1134);
+    // the 8s now also divide up the basins, shrinking the biggest ones
+    assert_eq!(part_2_with_wall(EXAMPLE, 8).unwrap(), 378);
+    let grid = parse_digit_grid(EXAMPLE).unwrap();
+    assert_eq!(basin_size(&grid, (2, 2), 9), 14);
+    assert!(basin_size(&grid, (2, 2), 8) < 14);
+}
+
+/// The size of the biggest basin.
+fn largest_basin(input: &str) -> Result<usize> {
+    let heightmap: Heightmap = input.parse()?;
+    heightmap
+        .minima()
         .iter()
-        .map(|(_, loc)| {
-            let size = basin_size(&grid, *loc);
-            size as u64
-        })
-        .collect::<Vec<_>>();
-    basin_sizes.sort();
+        .map(|&(_, loc)| heightmap.basin_size(loc))
+        .max()
+        .ok_or_else(|| anyhow!("No basins in the heightmap"))
+}
 
-    Ok(basin_sizes.into_iter().rev().take(3).product())
+#[test]
+fn test_largest_basin() {
+    assert_eq!(largest_basin(EXAMPLE).unwrap(), 14);
+    assert!(largest_basin("11\n11").is_err());
 }
 
 #[test]
@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Context, Result};
+
+/// Parses a rectangular grid of characters, one row per line, mapping each
+/// character to a cell with `f`. Surrounding whitespace is ignored, but every
+/// row must be the same width.
+pub fn parse_grid_of<T>(input: &str, f: impl Fn(char) -> Result<T>) -> Result<Vec<Vec<T>>> {
+    let mut grid: Vec<Vec<T>> = Vec::new();
+    for (y, line) in input.trim().lines().enumerate() {
+        let row = line
+            .chars()
+            .map(&f)
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("on line {}", y + 1))?;
+        if let Some(first) = grid.first() {
+            if row.len() != first.len() {
+                return Err(anyhow!(
+                    "ragged grid: line {} has {} cells, expected {}",
+                    y + 1,
+                    row.len(),
+                    first.len()
+                ));
+            }
+        }
+        grid.push(row);
+    }
+    Ok(grid)
+}
+
+/// Parses a grid of single decimal digits, the most common puzzle format.
+pub fn parse_digit_grid(input: &str) -> Result<Vec<Vec<u8>>> {
+    parse_grid_of(input, |c| {
+        c.to_digit(10)
+            .map(|d| d as u8)
+            .ok_or_else(|| anyhow!("Invalid digit: {}", c))
+    })
+}
+
+#[test]
+fn test_parse_grid_of() {
+    #[derive(Debug, PartialEq, Eq)]
+    enum Cell {
+        Empty,
+        East,
+        South,
+    }
+    let parse_cell = |c| match c {
+        '.' => Ok(Cell::Empty),
+        '>' => Ok(Cell::East),
+        'v' => Ok(Cell::South),
+        _ => Err(anyhow!("Invalid cell: {}", c)),
+    };
+    let grid = parse_grid_of(
+        "
+.>v
+v..
+",
+        parse_cell,
+    )
+    .unwrap();
+    assert_eq!(
+        grid,
+        vec![
+            vec![Cell::Empty, Cell::East, Cell::South],
+            vec![Cell::South, Cell::Empty, Cell::Empty],
+        ]
+    );
+
+    assert!(parse_grid_of(".>v\nv.", parse_cell).is_err());
+    assert!(parse_grid_of(".>v\nv.x", parse_cell).is_err());
+    assert_eq!(
+        parse_grid_of("", parse_cell).unwrap(),
+        Vec::<Vec<Cell>>::new()
+    );
+}
@@ -17,6 +17,7 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod grid;
 fn main() {
     println!("Hello, world!");
 }